@@ -0,0 +1,117 @@
+use crate::{Game, GameAction, GameAgent};
+use std::cell::RefCell;
+use std::io::{self, BufRead, StdinLock, Stdout, Write};
+use std::marker::PhantomData;
+
+/// An agent that asks a person for each move,
+/// listing the legal actions on `writer`, by `GameAction::describe`,
+/// and reading the chosen index from `reader`.
+pub struct HumanAgent<G: Game, R, W> {
+    reader: RefCell<R>,
+    writer: RefCell<W>,
+    g: PhantomData<G>,
+}
+
+impl<G: Game, R: BufRead, W: Write> HumanAgent<G, R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+            writer: RefCell::new(writer),
+            g: PhantomData,
+        }
+    }
+}
+
+impl<G: Game> HumanAgent<G, StdinLock<'static>, Stdout> {
+    /// An agent playing from the terminal.
+    pub fn stdio() -> Self {
+        Self::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<G: Game, R: BufRead, W: Write> HumanAgent<G, R, W> {
+    /// Prompts until a valid index is entered. Returns `None` if input runs out.
+    fn read_choice(&self, actions: &[G::GameAction]) -> io::Result<Option<usize>> {
+        let mut reader = self.reader.borrow_mut();
+        let mut writer = self.writer.borrow_mut();
+
+        for (index, action) in actions.iter().enumerate() {
+            writeln!(writer, "{}: {}", index, action.describe())?;
+        }
+
+        loop {
+            write!(writer, "Choose an action: ")?;
+            writer.flush()?;
+
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            match line.trim().parse::<usize>() {
+                Ok(index) if index < actions.len() => return Ok(Some(index)),
+                _ => writeln!(
+                    writer,
+                    "Please enter a number from 0 to {}.",
+                    actions.len() - 1
+                )?,
+            }
+        }
+    }
+}
+
+impl<G: Game, R: BufRead, W: Write> GameAgent<G> for HumanAgent<G, R, W> {
+    /// Panics if reading or writing fails, or if input ends before a choice is made,
+    /// since the game cannot continue without this player's move.
+    fn pick_action(&self, _: &G::GameState, actions: &[G::GameAction]) -> G::GameAction {
+        let index = self
+            .read_choice(actions)
+            .expect("Failed to prompt for an action.")
+            .expect("Input ended before an action was chosen.");
+
+        actions[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{SimpleGame, SimpleGameAction, SimpleGameState};
+    use crate::GameState;
+
+    fn pick(input: &str, output: &mut Vec<u8>) -> SimpleGameAction {
+        let state = SimpleGameState::new();
+        let agent = HumanAgent::<SimpleGame, _, _>::new(input.as_bytes(), output);
+
+        agent.pick_action(&state, &state.legal_actions())
+    }
+
+    #[test]
+    fn picks_the_entered_index() {
+        let mut output = Vec::new();
+
+        assert_eq!(SimpleGameAction::new(3), pick("1\n", &mut output));
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("0: bump 2\n1: bump 3\n2: bump 4\n"));
+    }
+
+    #[test]
+    fn reprompts_on_invalid_input() {
+        let mut output = Vec::new();
+
+        assert_eq!(SimpleGameAction::new(4), pick("x\n7\n2\n", &mut output));
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            2,
+            output.matches("Please enter a number from 0 to 2.").count()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Input ended")]
+    fn panics_when_input_ends() {
+        pick("", &mut Vec::new());
+    }
+}
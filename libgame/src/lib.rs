@@ -1,19 +1,142 @@
+//! Traits for describing turn-based games, and tools for playing them.
+//!
+//! A game is described by implementing `Game`, `GameState`, `GameAction`
+//! and `GameOutcome` for your own types, after which it can be played by
+//! `GameAgent`s through a `GameRunner`, or searched with `mcts::Mcts`.
+//!
+//! ```
+//! use libgame::mcts::Mcts;
+//! use libgame::{
+//!     Game, GameAction, GameAgent, GameOutcome, GameRunner, GameState, PlayerColor, PlayerId,
+//! };
+//!
+//! /// Players take turns removing one or two stones; whoever takes the last one wins.
+//! struct Nim;
+//!
+//! #[derive(Clone)]
+//! struct NimState {
+//!     stones: u32,
+//!     to_move: PlayerColor,
+//! }
+//!
+//! #[derive(Copy, Clone, PartialEq)]
+//! struct Take(u32);
+//!
+//! #[derive(Copy, Clone, PartialEq)]
+//! struct Winner(PlayerColor);
+//!
+//! impl GameAction for Take {}
+//!
+//! impl GameOutcome for Winner {
+//!     fn winner(&self) -> Option<PlayerId> {
+//!         Some(self.0.into())
+//!     }
+//! }
+//!
+//! impl GameState for NimState {
+//!     type Action = Take;
+//!     type Outcome = Winner;
+//!
+//!     fn make_next(&mut self, action: Take) {
+//!         self.stones -= action.0;
+//!         self.to_move = self.to_move.opponent();
+//!     }
+//!
+//!     fn legal_actions(&self) -> Vec<Take> {
+//!         (1..=self.stones.min(2)).map(Take).collect()
+//!     }
+//!
+//!     fn current_player_turn(&self) -> PlayerId {
+//!         self.to_move.into()
+//!     }
+//!
+//!     fn outcome(&self) -> Option<Winner> {
+//!         // The player who took the last stone is the one not to move.
+//!         match self.stones {
+//!             0 => Some(Winner(self.to_move.opponent())),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! impl Game for Nim {
+//!     type GameState = NimState;
+//!     type GameAction = Take;
+//!     type GameOutcome = Winner;
+//! }
+//!
+//! struct TakeOne;
+//!
+//! impl GameAgent<Nim> for TakeOne {
+//!     fn pick_action(&self, _: &NimState, actions: &[Take]) -> Take {
+//!         actions[0]
+//!     }
+//! }
+//!
+//! let start = NimState {
+//!     stones: 4,
+//!     to_move: PlayerColor::Black,
+//! };
+//!
+//! GameRunner::<Nim>::new(vec![Box::new(TakeOne), Box::new(TakeOne)], start.clone()).play();
+//!
+//! // With 4 stones, taking 1 leaves the opponent a losing 3.
+//! let mut mcts = Mcts::<Nim>::default();
+//! assert!(mcts.search(start, 2_000) == Take(1));
+//! ```
+
+mod human_agent;
+pub mod mcts;
+mod random_agent;
+mod tournament;
+
+pub use human_agent::HumanAgent;
+pub use random_agent::RandomAgent;
+pub use tournament::{elo_ratings, AgentId, MatchRecord, Standing, Tournament, TournamentResults};
+
+use rand::Rng;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 pub trait Game {
-    type GameState: GameState<Action = Self::GameAction>;
+    type GameState: GameState<Action = Self::GameAction, Outcome = Self::GameOutcome>;
     type GameAction: GameAction;
     type GameOutcome: GameOutcome;
 }
 
-#[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
-enum PlayerColor {
+/// Identifies a player by their index in turn order, starting from 0.
+#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+pub struct PlayerId(pub usize);
+
+/// The players of a two-player game, as `PlayerId`s 0 and 1.
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
+pub enum PlayerColor {
     Black,
     White,
 }
 
+impl PlayerColor {
+    pub fn opponent(self) -> Self {
+        match self {
+            PlayerColor::Black => PlayerColor::White,
+            PlayerColor::White => PlayerColor::Black,
+        }
+    }
+}
+
+impl From<PlayerColor> for PlayerId {
+    fn from(color: PlayerColor) -> Self {
+        match color {
+            PlayerColor::Black => PlayerId(0),
+            PlayerColor::White => PlayerId(1),
+        }
+    }
+}
+
 /// A trait describing a game's state.
 /// A GameState can be updated to its next state
 /// by applying a GameAction.
-trait GameState: Clone + Sized {
+pub trait GameState: Clone + Sized {
     type Action: GameAction;
     type Outcome: GameOutcome;
 
@@ -23,83 +146,472 @@ trait GameState: Clone + Sized {
         next
     }
 
+    /// The state reached by playing `actions` in order from this one,
+    /// as for setting up test positions or following an opening book.
+    fn apply_all(&self, actions: impl IntoIterator<Item = Self::Action>) -> Self {
+        actions.into_iter().fold(self.clone(), |mut state, action| {
+            state.make_next(action);
+            state
+        })
+    }
+
     fn make_next(&mut self, action: Self::Action);
 
-    fn legal_actions(&self) -> Vec<Self::Action>;
+    /// Implement at least one of `legal_actions` and `legal_actions_into`,
+    /// since each is provided in terms of the other.
+    fn legal_actions(&self) -> Vec<Self::Action> {
+        let mut actions = Vec::new();
+        self.legal_actions_into(&mut actions);
+        actions
+    }
+
+    /// Replaces the contents of `buf` with the legal actions,
+    /// letting hot loops reuse one allocation.
+    /// Override this, rather than `legal_actions`, to avoid allocating at all.
+    fn legal_actions_into(&self, buf: &mut Vec<Self::Action>) {
+        buf.clear();
+        buf.extend(self.legal_actions());
+    }
 
-    fn current_player_turn(&self) -> PlayerColor;
+    fn current_player_turn(&self) -> PlayerId;
 
     fn outcome(&self) -> Option<Self::Outcome>;
+
+    /// For a state where chance acts next, such as a dice roll or a card draw,
+    /// every possible event as an action with its probability, summing to 1.
+    /// `None`, the default, for states where a player chooses the action.
+    /// MCTS samples these events itself, while `GameRunner` still asks
+    /// the agent for `current_player_turn` to pick one.
+    fn chance_outcomes(&self) -> Option<Vec<(Self::Action, f64)>> {
+        None
+    }
+
+    /// For a game of imperfect information, a complete state consistent with
+    /// everything the player to move can see in this one, with whatever is
+    /// hidden from them, like the other players' cards, sampled using `rng`.
+    /// Used by `Mcts::search_ismcts`. The default, for games of perfect
+    /// information, is the state itself.
+    fn determinize(&self, rng: &mut impl Rng) -> Self {
+        let _ = rng;
+        self.clone()
+    }
+
+    /// A representative of every state equivalent to this one under the game's
+    /// symmetries, such as the rotations and reflections of a board,
+    /// which transposition tables key off so equivalent states share statistics.
+    /// Must be idempotent: the canonical form of a canonical state is itself.
+    /// The default, for games without symmetries, is the state itself.
+    fn canonicalize(&self) -> Self {
+        self.clone()
+    }
+
+    /// Whether the game has ended.
+    /// Override this if it is cheaper than computing the full outcome.
+    fn is_terminal(&self) -> bool {
+        self.outcome().is_some()
+    }
+}
+
+/// A game state that can be identified by a 64-bit hash,
+/// which games opt into for transposition tables and repetition detection.
+pub trait HashableState: GameState {
+    /// Equal states must produce equal hashes,
+    /// however the sequence of actions that reached them.
+    /// Distinct states should rarely collide, since a collision
+    /// makes them indistinguishable to anything keyed off this hash.
+    fn zobrist_hash(&self) -> u64;
 }
 
 /// A trait describing a game's action,
 /// which is the input from a Player that updates
 /// a GameState from one state to the next.
-trait GameAction: Copy {}
+pub trait GameAction: Copy + PartialEq {
+    /// A human-readable name for the action, such as move notation like "e2e4",
+    /// as shown by `HumanAgent`.
+    /// The default is a placeholder that does not distinguish one action from another.
+    fn describe(&self) -> String {
+        String::from("<action>")
+    }
+}
+
+/// A GameAction whose every possible value maps to a distinct index
+/// below `MAX_ACTIONS`, so per-action data can be stored in dense arrays,
+/// such as the policy output of a neural network.
+pub trait Indexable: GameAction {
+    /// One more than the largest index of any action.
+    const MAX_ACTIONS: usize;
+
+    fn to_index(&self) -> usize;
+
+    /// The action at `index`, or `None` if no action has that index.
+    fn from_index(index: usize) -> Option<Self>;
+}
 
 /// A trait describing the final outcome of a Game, after it is played to completion.
-trait GameOutcome: Copy {
-    fn is_final(&self) -> bool;
+pub trait GameOutcome: Copy {
+    /// Whether the game is over for good with this outcome.
+    /// An outcome is by definition that of a completed game, so this is true by default;
+    /// override it only for a game that also reports provisional outcomes,
+    /// such as a standing score partway through, returning false for those.
+    fn is_final(&self) -> bool {
+        true
+    }
+
+    /// The player who won, or `None` if nobody did, as in a draw.
+    fn winner(&self) -> Option<PlayerId>;
+
+    /// How good the outcome is for `player`, from 0 for a loss to 1 for a win.
+    /// By default, this is 1 if `player` won, 0 if another player did,
+    /// and 0.5 if nobody did. Override it for outcomes with finer distinctions,
+    /// such as margins of victory or placings among several players.
+    fn value_for(&self, player: PlayerId) -> f64 {
+        match self.winner() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
 }
 
 /// A trait describing an agent.
 /// A GameAgent is shown a GameState,
 /// and from that GameState it picks the
 /// GameAction it wants to take in that GameState.
-trait GameAgent<G: Game> {
+pub trait GameAgent<G: Game> {
     fn pick_action(&self, state: &G::GameState, actions: &[G::GameAction]) -> G::GameAction;
 }
 
-struct GameRunner<G: Game> {
-    black_agent: Box<dyn GameAgent<G>>,
-    white_agent: Box<dyn GameAgent<G>>,
+/// A GameAgent that awaits its choice of action,
+/// such as one backed by a remote engine or an inference server.
+/// Played with `GameRunner::play_async`.
+// The returned future has no `Send` bound, so agents may hold `Rc`s,
+// but `play_async` cannot be spawned onto a multi-threaded executor.
+#[allow(async_fn_in_trait)]
+pub trait AsyncGameAgent<G: Game> {
+    async fn pick_action(&self, state: &G::GameState, actions: &[G::GameAction]) -> G::GameAction;
+}
+
+type Observer<G> = Box<dyn FnMut(&<G as Game>::GameState, <G as Game>::GameAction)>;
+
+/// Plays a game to completion, asking each player's agent for their moves.
+pub struct GameRunner<G: Game> {
+    agents: Vec<Box<dyn GameAgent<G>>>,
     game_state: G::GameState,
+    history: Vec<G::GameAction>,
+    observers: Vec<Observer<G>>,
+    time_control: Option<Duration>,
+    clocks: Vec<Duration>,
+    max_moves: Option<usize>,
+    repetitions: Option<Repetitions<G::GameState>>,
+}
+
+/// Counts how often each position has occurred, by `HashableState::zobrist_hash`.
+struct Repetitions<S> {
+    limit: usize,
+    hash: fn(&S) -> u64,
+    seen: HashMap<u64, usize>,
+}
+
+impl<S> Repetitions<S> {
+    /// Records another occurrence of `state`, returning how many there have been.
+    fn record(&mut self, state: &S) -> usize {
+        let count = self.seen.entry((self.hash)(state)).or_insert(0);
+        *count += 1;
+        *count
+    }
+}
+
+/// How a game played by `GameRunner` ended.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GameResult<O> {
+    /// The game reached a terminal state with this outcome.
+    Finished(O),
+    /// This player's clock ran out, losing them the game.
+    TimedOut(PlayerId),
+    /// The runner declared a draw before the game reached a terminal state.
+    Drawn(DrawReason),
+}
+
+/// Why `GameRunner` cut a game short with a draw.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DrawReason {
+    /// The configured maximum number of moves was played.
+    MoveLimit,
+    /// A position occurred the configured number of times.
+    Repetition,
+}
+
+/// The record of a finished game, as returned by `GameRunner::play`.
+pub struct GameRecord<G: Game> {
+    /// Every action played, in order.
+    pub history: Vec<G::GameAction>,
+    pub final_state: G::GameState,
+    pub result: GameResult<G::GameOutcome>,
 }
 
 impl<G: Game> GameRunner<G> {
-    pub fn new(
-        black_agent: Box<dyn GameAgent<G>>,
-        white_agent: Box<dyn GameAgent<G>>,
-        start_state: G::GameState,
-    ) -> Self {
+    /// `agents[i]` plays for `PlayerId(i)`.
+    pub fn new(agents: Vec<Box<dyn GameAgent<G>>>, start_state: G::GameState) -> Self {
         Self {
-            black_agent,
-            white_agent,
+            agents,
             game_state: start_state,
+            history: Vec::new(),
+            observers: Vec::new(),
+            time_control: None,
+            clocks: Vec::new(),
+            max_moves: None,
+            repetitions: None,
         }
     }
 
-    pub fn play(mut self) {
-        while self.game_state.outcome().is_none() {
-            let active_player = match self.game_state.current_player_turn() {
-                PlayerColor::Black => &self.black_agent,
-                PlayerColor::White => &self.white_agent,
-            };
+    /// Declares a draw once `max_moves` moves have been played,
+    /// so that games which might never end still finish.
+    pub fn with_max_moves(mut self, max_moves: usize) -> Self {
+        self.max_moves = Some(max_moves);
+        self
+    }
+
+    /// Gives every player a chess-style clock starting at `budget`.
+    /// A player's clock runs while their agent picks an action,
+    /// and a player whose clock reaches zero loses on time.
+    pub fn with_time_control(mut self, budget: Duration) -> Self {
+        self.time_control = Some(budget);
+        self.clocks = vec![budget; self.agents.len()];
+        self
+    }
+
+    /// The time left on `player`'s clock, or `None` without time control.
+    pub fn remaining_time(&self, player: PlayerId) -> Option<Duration> {
+        self.time_control
+            .and_then(|_| self.clocks.get(player.0).copied())
+    }
+
+    /// Registers `observer` to be called after every move,
+    /// with the state the move led to and the action played.
+    /// Observers are called in the order they were registered.
+    pub fn with_observer(mut self, observer: Observer<G>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// The actions played so far, in order.
+    pub fn history(&self) -> &[G::GameAction] {
+        &self.history
+    }
+
+    /// The current state of the game.
+    pub fn state(&self) -> &G::GameState {
+        &self.game_state
+    }
+
+    /// Plays until the game ends, returning a record of the whole game.
+    /// With time control, a move that runs out the mover's clock is not played.
+    /// Panics if it becomes the turn of a player without an agent.
+    pub fn play(&mut self) -> GameRecord<G> {
+        while !self.game_state.is_terminal() {
+            if let Some(reason) = self.forced_draw() {
+                return self.record(GameResult::Drawn(reason));
+            }
+
+            let player = self.game_state.current_player_turn();
+            let active_player = agent_for(&self.agents, player);
 
             let legal_actions = self.game_state.legal_actions();
+            let started = Instant::now();
             let selected_action = active_player.pick_action(&self.game_state, &legal_actions);
-            self.game_state.make_next(selected_action);
+
+            if let Some(result) = self.apply_move(player, started, selected_action) {
+                return self.record(result);
+            }
+        }
+
+        self.finished_record()
+    }
+
+    /// Like `play`, but for agents that must be awaited.
+    /// Async agents cannot be boxed as trait objects,
+    /// so they are passed here instead of to `new`, with `agents[i]`
+    /// playing for `PlayerId(i)`; combine differing agents in an enum.
+    pub async fn play_async<A: AsyncGameAgent<G>>(&mut self, agents: &[A]) -> GameRecord<G> {
+        if let Some(budget) = self.time_control {
+            if self.clocks.len() < agents.len() {
+                self.clocks.resize(agents.len(), budget);
+            }
+        }
+
+        while !self.game_state.is_terminal() {
+            if let Some(reason) = self.forced_draw() {
+                return self.record(GameResult::Drawn(reason));
+            }
+
+            let player = self.game_state.current_player_turn();
+            let active_player = agent_for(agents, player);
+
+            let legal_actions = self.game_state.legal_actions();
+            let started = Instant::now();
+            let selected_action = active_player
+                .pick_action(&self.game_state, &legal_actions)
+                .await;
+
+            if let Some(result) = self.apply_move(player, started, selected_action) {
+                return self.record(result);
+            }
+        }
+
+        self.finished_record()
+    }
+
+    /// Charges `player`'s clock for the move they started choosing at `started`,
+    /// then plays it, unless it ran out their time.
+    fn apply_move(
+        &mut self,
+        player: PlayerId,
+        started: Instant,
+        action: G::GameAction,
+    ) -> Option<GameResult<G::GameOutcome>> {
+        if let Some(remaining) = self.time_control.and(self.clocks.get_mut(player.0)) {
+            *remaining = remaining.saturating_sub(started.elapsed());
+
+            if *remaining == Duration::ZERO {
+                return Some(GameResult::TimedOut(player));
+            }
+        }
+
+        self.game_state.make_next(action);
+        self.history.push(action);
+
+        if let Some(repetitions) = self.repetitions.as_mut() {
+            repetitions.record(&self.game_state);
+        }
+
+        for observer in &mut self.observers {
+            observer(&self.game_state, action);
         }
+
+        None
     }
+
+    /// Whether the move limit or repetition rule ends the game as a draw.
+    fn forced_draw(&self) -> Option<DrawReason> {
+        if self.max_moves.is_some_and(|max| self.history.len() >= max) {
+            return Some(DrawReason::MoveLimit);
+        }
+
+        let repeated = self.repetitions.as_ref().is_some_and(|r| {
+            r.seen
+                .get(&(r.hash)(&self.game_state))
+                .is_some_and(|&count| count >= r.limit)
+        });
+
+        repeated.then_some(DrawReason::Repetition)
+    }
+
+    fn finished_record(&self) -> GameRecord<G> {
+        let outcome = self
+            .game_state
+            .outcome()
+            .expect("A terminal state must have an outcome.");
+
+        self.record(GameResult::Finished(outcome))
+    }
+
+    fn record(&self, result: GameResult<G::GameOutcome>) -> GameRecord<G> {
+        GameRecord {
+            history: self.history.clone(),
+            final_state: self.game_state.clone(),
+            result,
+        }
+    }
+}
+
+/// Every state of a game played from `start` by `actions`,
+/// beginning with `start` itself and ending with the state after the last action.
+/// Panics if an action is not legal in the state it is applied to,
+/// since a record that does not replay is corrupt rather than recoverable.
+pub fn replay<G: Game>(start: G::GameState, actions: &[G::GameAction]) -> Vec<G::GameState> {
+    let mut states = Vec::with_capacity(actions.len() + 1);
+    states.push(start);
+
+    for (index, &action) in actions.iter().enumerate() {
+        let state = states.last().unwrap();
+
+        if !state.legal_actions().contains(&action) {
+            panic!("Action {} of the replay is illegal in its state.", index);
+        }
+
+        let next = state.next(action);
+        states.push(next);
+    }
+
+    states
+}
+
+/// Counts the leaves of the game tree `depth` moves below `state`,
+/// counting a terminal state reached early as a single leaf.
+/// Comparing these counts to known values is a standard check of move generation.
+pub fn perft<G: Game>(state: &G::GameState, depth: usize) -> u64 {
+    if depth == 0 || state.is_terminal() {
+        return 1;
+    }
+
+    state
+        .legal_actions()
+        .into_iter()
+        .map(|action| perft::<G>(&state.next(action), depth - 1))
+        .sum()
+}
+
+impl<G: Game> GameRunner<G>
+where
+    G::GameState: HashableState,
+{
+    /// Declares a draw once the same state, as identified by its `zobrist_hash`,
+    /// has occurred `times` times, counting the starting state.
+    pub fn with_repetition_limit(mut self, times: usize) -> Self {
+        let mut repetitions = Repetitions {
+            limit: times,
+            hash: G::GameState::zobrist_hash,
+            seen: HashMap::new(),
+        };
+        repetitions.record(&self.game_state);
+
+        self.repetitions = Some(repetitions);
+        self
+    }
+}
+
+fn agent_for<A>(agents: &[A], PlayerId(player): PlayerId) -> &A {
+    agents
+        .get(player)
+        .unwrap_or_else(|| panic!("No agent for player {}.", player))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
     use std::default::Default;
     use std::marker::PhantomData;
+    use std::rc::Rc;
 
     #[derive(Default, Debug)]
-    struct SimpleGame;
+    pub(crate) struct SimpleGame;
 
-    #[derive(Debug, Clone)]
-    struct SimpleGameState {
-        num: usize,
-        cur_player: PlayerColor,
+    #[derive(Debug, Clone, Hash)]
+    pub(crate) struct SimpleGameState {
+        pub(crate) num: usize,
+        pub(crate) cur_player: PlayerColor,
+    }
+
+    impl HashableState for SimpleGameState {
+        fn zobrist_hash(&self) -> u64 {
+            let player: PlayerId = self.cur_player.into();
+            (self.num as u64) << 1 | player.0 as u64
+        }
     }
 
     impl SimpleGameState {
-        fn new() -> Self {
+        pub(crate) fn new() -> Self {
             Self {
                 num: 0,
                 cur_player: PlayerColor::Black,
@@ -107,19 +619,34 @@ mod tests {
         }
     }
 
-    #[derive(Copy, Clone)]
-    struct SimpleGameAction {
-        bump: usize,
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    pub(crate) struct SimpleGameAction {
+        pub(crate) bump: usize,
     }
 
     impl SimpleGameAction {
-        fn new(bump: usize) -> Self {
+        pub(crate) fn new(bump: usize) -> Self {
             Self { bump }
         }
     }
 
-    #[derive(Copy, Clone)]
-    enum SimpleGameOutcome {
+    impl Indexable for SimpleGameAction {
+        const MAX_ACTIONS: usize = 3;
+
+        fn to_index(&self) -> usize {
+            self.bump - 2
+        }
+
+        fn from_index(index: usize) -> Option<Self> {
+            match index {
+                0..=2 => Some(Self::new(index + 2)),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    pub(crate) enum SimpleGameOutcome {
         BlackWins,
         WhiteWins,
         BothLose,
@@ -130,11 +657,19 @@ mod tests {
         g: PhantomData<G>,
     }
 
-    impl GameAction for SimpleGameAction {}
+    impl GameAction for SimpleGameAction {
+        fn describe(&self) -> String {
+            format!("bump {}", self.bump)
+        }
+    }
 
     impl GameOutcome for SimpleGameOutcome {
-        fn is_final(&self) -> bool {
-            todo!()
+        fn winner(&self) -> Option<PlayerId> {
+            match self {
+                SimpleGameOutcome::BlackWins => Some(PlayerColor::Black.into()),
+                SimpleGameOutcome::WhiteWins => Some(PlayerColor::White.into()),
+                SimpleGameOutcome::BothLose => None,
+            }
         }
     }
 
@@ -154,8 +689,8 @@ mod tests {
             ]
         }
 
-        fn current_player_turn(&self) -> PlayerColor {
-            self.cur_player
+        fn current_player_turn(&self) -> PlayerId {
+            self.cur_player.into()
         }
 
         fn outcome(&self) -> Option<Self::Outcome> {
@@ -174,6 +709,122 @@ mod tests {
         }
     }
 
+    /// A game that never ends, counting how many moves have been made.
+    pub(crate) struct EndlessGame;
+
+    #[derive(Clone, Default)]
+    pub(crate) struct EndlessState {
+        pub(crate) moves: usize,
+    }
+
+    #[derive(Copy, Clone, PartialEq)]
+    pub(crate) struct EndlessAction;
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub(crate) struct EndlessOutcome {
+        pub(crate) moves: usize,
+    }
+
+    impl GameAction for EndlessAction {}
+
+    impl GameOutcome for EndlessOutcome {
+        fn winner(&self) -> Option<PlayerId> {
+            None
+        }
+    }
+
+    impl GameState for EndlessState {
+        type Action = EndlessAction;
+        type Outcome = EndlessOutcome;
+
+        fn make_next(&mut self, _: Self::Action) {
+            self.moves += 1;
+        }
+
+        fn legal_actions(&self) -> Vec<Self::Action> {
+            vec![EndlessAction, EndlessAction]
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<Self::Outcome> {
+            None
+        }
+    }
+
+    impl Game for EndlessGame {
+        type GameState = EndlessState;
+        type GameAction = EndlessAction;
+        type GameOutcome = EndlessOutcome;
+    }
+
+    pub(crate) fn count_moves(state: &EndlessState) -> EndlessOutcome {
+        EndlessOutcome { moves: state.moves }
+    }
+
+    /// Black makes one move, choosing either to win or to draw.
+    pub(crate) struct DrawGame;
+
+    #[derive(Clone, Default)]
+    pub(crate) struct DrawState {
+        pub(crate) result: Option<DrawOutcome>,
+    }
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    pub(crate) enum DrawAction {
+        Win,
+        Draw,
+    }
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    pub(crate) enum DrawOutcome {
+        BlackWins,
+        Drawn,
+    }
+
+    impl GameAction for DrawAction {}
+
+    impl GameOutcome for DrawOutcome {
+        fn winner(&self) -> Option<PlayerId> {
+            match self {
+                DrawOutcome::BlackWins => Some(PlayerColor::Black.into()),
+                DrawOutcome::Drawn => None,
+            }
+        }
+    }
+
+    impl GameState for DrawState {
+        type Action = DrawAction;
+        type Outcome = DrawOutcome;
+
+        fn make_next(&mut self, action: Self::Action) {
+            self.result = Some(match action {
+                DrawAction::Win => DrawOutcome::BlackWins,
+                DrawAction::Draw => DrawOutcome::Drawn,
+            });
+        }
+
+        fn legal_actions(&self) -> Vec<Self::Action> {
+            vec![DrawAction::Draw, DrawAction::Win]
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<Self::Outcome> {
+            self.result
+        }
+    }
+
+    impl Game for DrawGame {
+        type GameState = DrawState;
+        type GameAction = DrawAction;
+        type GameOutcome = DrawOutcome;
+    }
+
     impl<G: Game> GameAgent<G> for SimpleAgent<G> {
         fn pick_action(&self, _: &G::GameState, actions: &[G::GameAction]) -> G::GameAction {
             actions[0]
@@ -186,6 +837,42 @@ mod tests {
         type GameOutcome = SimpleGameOutcome;
     }
 
+    #[test]
+    fn outcome_value_is_from_each_players_perspective() {
+        let black = PlayerColor::Black.into();
+        let white = PlayerColor::White.into();
+
+        assert_eq!(1.0, SimpleGameOutcome::BlackWins.value_for(black));
+        assert_eq!(0.0, SimpleGameOutcome::BlackWins.value_for(white));
+        assert_eq!(1.0, SimpleGameOutcome::WhiteWins.value_for(white));
+        assert_eq!(0.5, SimpleGameOutcome::BothLose.value_for(black));
+    }
+
+    #[test]
+    fn outcome_value_counts_draws_at_half() {
+        assert_eq!(
+            1.0,
+            DrawOutcome::BlackWins.value_for(PlayerColor::Black.into())
+        );
+        assert_eq!(
+            0.0,
+            DrawOutcome::BlackWins.value_for(PlayerColor::White.into())
+        );
+        assert_eq!(0.5, DrawOutcome::Drawn.value_for(PlayerColor::Black.into()));
+        assert_eq!(0.5, DrawOutcome::Drawn.value_for(PlayerColor::White.into()));
+    }
+
+    #[test]
+    fn every_simple_outcome_is_final() {
+        let outcomes = [
+            SimpleGameOutcome::BlackWins,
+            SimpleGameOutcome::WhiteWins,
+            SimpleGameOutcome::BothLose,
+        ];
+
+        assert!(outcomes.iter().all(|o| o.is_final()));
+    }
+
     #[test]
     fn it_works() {
         let black_agent = Box::new(SimpleAgent::<SimpleGame>::default());
@@ -193,8 +880,452 @@ mod tests {
 
         let start_state = SimpleGameState::new();
 
-        let runner = GameRunner::new(black_agent, white_agent, start_state);
+        let mut runner = GameRunner::new(vec![black_agent, white_agent], start_state);
 
         runner.play();
     }
+
+    #[test]
+    fn observers_see_every_move() {
+        let agents: Vec<Box<dyn GameAgent<SimpleGame>>> = vec![
+            Box::new(SimpleAgent::default()),
+            Box::new(SimpleAgent::default()),
+        ];
+        let count = Rc::new(RefCell::new(0));
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let mut runner = {
+            let count = Rc::clone(&count);
+            let seen = Rc::clone(&seen);
+            GameRunner::new(agents, SimpleGameState::new())
+                .with_observer(Box::new(move |_, _| *count.borrow_mut() += 1))
+                .with_observer(Box::new(move |state, action| {
+                    seen.borrow_mut().push((state.num, action.bump))
+                }))
+        };
+
+        let record = runner.play();
+
+        assert_eq!(record.history.len(), *count.borrow());
+
+        let seen = seen.borrow();
+        assert_eq!(record.history.len(), seen.len());
+        assert_eq!((2, 2), seen[0]);
+        assert_eq!((42, 2), *seen.last().unwrap());
+    }
+
+    struct SlowAgent(Duration);
+
+    impl<G: Game> GameAgent<G> for SlowAgent {
+        fn pick_action(&self, _: &G::GameState, actions: &[G::GameAction]) -> G::GameAction {
+            std::thread::sleep(self.0);
+            actions[0]
+        }
+    }
+
+    #[test]
+    fn slow_agent_loses_on_time() {
+        let agents: Vec<Box<dyn GameAgent<SimpleGame>>> = vec![
+            Box::new(SlowAgent(Duration::from_millis(20))),
+            Box::new(SimpleAgent::default()),
+        ];
+        let budget = Duration::from_millis(5);
+        let mut runner = GameRunner::new(agents, SimpleGameState::new()).with_time_control(budget);
+
+        let record = runner.play();
+
+        assert_eq!(GameResult::TimedOut(PlayerId(0)), record.result);
+        assert!(record.history.is_empty());
+        assert_eq!(Some(Duration::ZERO), runner.remaining_time(PlayerId(0)));
+        assert_eq!(Some(budget), runner.remaining_time(PlayerId(1)));
+    }
+
+    #[test]
+    fn quick_agents_finish_within_their_clocks() {
+        let agents: Vec<Box<dyn GameAgent<SimpleGame>>> = vec![
+            Box::new(SimpleAgent::default()),
+            Box::new(SimpleAgent::default()),
+        ];
+        let budget = Duration::from_secs(60);
+        let mut runner = GameRunner::new(agents, SimpleGameState::new()).with_time_control(budget);
+
+        let record = runner.play();
+
+        assert_eq!(
+            GameResult::Finished(SimpleGameOutcome::BlackWins),
+            record.result
+        );
+        assert!(runner.remaining_time(PlayerId(0)).unwrap() <= budget);
+        assert_eq!(None, runner.remaining_time(PlayerId(2)));
+    }
+
+    struct FirstActionAsync;
+
+    impl<G: Game> AsyncGameAgent<G> for FirstActionAsync {
+        async fn pick_action(&self, _: &G::GameState, actions: &[G::GameAction]) -> G::GameAction {
+            actions[0]
+        }
+    }
+
+    #[test]
+    fn play_async_awaits_agents_to_completion() {
+        let mut runner = GameRunner::<SimpleGame>::new(Vec::new(), SimpleGameState::new());
+
+        let record =
+            futures::executor::block_on(runner.play_async(&[FirstActionAsync, FirstActionAsync]));
+
+        assert_eq!(21, record.history.len());
+        assert_eq!(42, record.final_state.num);
+        assert_eq!(
+            GameResult::Finished(SimpleGameOutcome::BlackWins),
+            record.result
+        );
+    }
+
+    #[test]
+    fn play_records_every_move() {
+        let agents: Vec<Box<dyn GameAgent<SimpleGame>>> = vec![
+            Box::new(SimpleAgent::default()),
+            Box::new(SimpleAgent::default()),
+        ];
+        let mut runner = GameRunner::new(agents, SimpleGameState::new());
+
+        let record = runner.play();
+
+        // Always bumping by 2 takes 21 moves to reach 42.
+        assert_eq!(21, record.history.len());
+        assert_eq!(record.history, runner.history());
+        assert_eq!(
+            GameResult::Finished(SimpleGameOutcome::BlackWins),
+            record.result
+        );
+
+        let mut replayed = SimpleGameState::new();
+        for &action in &record.history {
+            replayed.make_next(action);
+        }
+        assert_eq!(record.final_state.num, replayed.num);
+        assert_eq!(runner.state().num, replayed.num);
+    }
+
+    #[test]
+    fn replay_reconstructs_every_state() {
+        let agents: Vec<Box<dyn GameAgent<SimpleGame>>> = vec![
+            Box::new(RandomAgent::with_seed(3)),
+            Box::new(RandomAgent::with_seed(4)),
+        ];
+        let record = GameRunner::new(agents, SimpleGameState::new()).play();
+
+        let states = replay::<SimpleGame>(SimpleGameState::new(), &record.history);
+
+        assert_eq!(record.history.len() + 1, states.len());
+        assert_eq!(0, states[0].num);
+        assert_eq!(record.history[0].bump, states[1].num);
+        assert_eq!(record.final_state.num, states.last().unwrap().num);
+    }
+
+    #[test]
+    #[should_panic(expected = "Action 1 of the replay is illegal")]
+    fn replay_panics_on_illegal_action() {
+        let actions = [SimpleGameAction::new(2), SimpleGameAction::new(5)];
+
+        replay::<SimpleGame>(SimpleGameState::new(), &actions);
+    }
+
+    #[test]
+    fn actions_round_trip_through_indices() {
+        for action in SimpleGameState::new().legal_actions() {
+            let index = action.to_index();
+
+            assert!(index < SimpleGameAction::MAX_ACTIONS);
+            assert_eq!(Some(action), SimpleGameAction::from_index(index));
+        }
+
+        assert_eq!(
+            None,
+            SimpleGameAction::from_index(SimpleGameAction::MAX_ACTIONS)
+        );
+    }
+
+    #[test]
+    fn legal_actions_into_matches_legal_actions() {
+        let state = SimpleGameState::new();
+        let mut buf = vec![SimpleGameAction::new(99)];
+
+        state.legal_actions_into(&mut buf);
+
+        assert_eq!(state.legal_actions(), buf);
+    }
+
+    /// A state with a fixed number of moves, generated only by `legal_actions_into`.
+    #[derive(Clone)]
+    struct BufferedState(usize);
+
+    impl GameState for BufferedState {
+        type Action = EndlessAction;
+        type Outcome = EndlessOutcome;
+
+        fn make_next(&mut self, _: Self::Action) {}
+
+        fn legal_actions_into(&self, buf: &mut Vec<Self::Action>) {
+            buf.clear();
+            buf.resize(self.0, EndlessAction);
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<Self::Outcome> {
+            None
+        }
+    }
+
+    #[test]
+    fn legal_actions_defaults_to_legal_actions_into() {
+        assert_eq!(3, BufferedState(3).legal_actions().len());
+    }
+
+    #[test]
+    fn perft_counts_simple_game_leaves() {
+        let start = SimpleGameState::new();
+
+        assert_eq!(1, perft::<SimpleGame>(&start, 0));
+        assert_eq!(3, perft::<SimpleGame>(&start, 1));
+        assert_eq!(9, perft::<SimpleGame>(&start, 2));
+        assert_eq!(27, perft::<SimpleGame>(&start, 3));
+    }
+
+    #[test]
+    fn describe_names_actions() {
+        assert_eq!("bump 3", SimpleGameAction::new(3).describe());
+        assert_eq!("<action>", EndlessAction.describe());
+    }
+
+    #[test]
+    fn apply_all_plays_actions_in_order() {
+        let bumps = [2, 4, 3];
+
+        let state =
+            SimpleGameState::new().apply_all(bumps.iter().map(|&b| SimpleGameAction::new(b)));
+
+        assert_eq!(bumps.iter().sum::<usize>(), state.num);
+    }
+
+    #[test]
+    fn perft_stops_at_terminal_states() {
+        // Every move from 40 ends the game.
+        let near_end = SimpleGameState {
+            num: 40,
+            cur_player: PlayerColor::Black,
+        };
+
+        assert_eq!(3, perft::<SimpleGame>(&near_end, 3));
+    }
+
+    #[test]
+    fn move_limit_draws_endless_game() {
+        let agents: Vec<Box<dyn GameAgent<EndlessGame>>> =
+            vec![Box::new(SimpleAgent { g: PhantomData })];
+        let mut runner = GameRunner::new(agents, EndlessState::default()).with_max_moves(10);
+
+        let record = runner.play();
+
+        assert_eq!(GameResult::Drawn(DrawReason::MoveLimit), record.result);
+        assert_eq!(10, record.history.len());
+        assert_eq!(10, record.final_state.moves);
+    }
+
+    /// A piece shuttles back and forth between two squares forever.
+    struct ShuttleGame;
+
+    #[derive(Clone, Default)]
+    struct ShuttleState {
+        on_far_square: bool,
+    }
+
+    impl GameState for ShuttleState {
+        type Action = EndlessAction;
+        type Outcome = EndlessOutcome;
+
+        fn make_next(&mut self, _: Self::Action) {
+            self.on_far_square = !self.on_far_square;
+        }
+
+        fn legal_actions(&self) -> Vec<Self::Action> {
+            vec![EndlessAction]
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<Self::Outcome> {
+            None
+        }
+    }
+
+    impl HashableState for ShuttleState {
+        fn zobrist_hash(&self) -> u64 {
+            self.on_far_square as u64
+        }
+    }
+
+    impl Game for ShuttleGame {
+        type GameState = ShuttleState;
+        type GameAction = EndlessAction;
+        type GameOutcome = EndlessOutcome;
+    }
+
+    #[test]
+    fn repeated_position_draws() {
+        let agents: Vec<Box<dyn GameAgent<ShuttleGame>>> =
+            vec![Box::new(SimpleAgent { g: PhantomData })];
+        let mut runner = GameRunner::new(agents, ShuttleState::default()).with_repetition_limit(3);
+
+        let record = runner.play();
+
+        // The starting square recurs for the third time after four moves.
+        assert_eq!(GameResult::Drawn(DrawReason::Repetition), record.result);
+        assert_eq!(4, record.history.len());
+        assert!(!record.final_state.on_far_square);
+    }
+
+    /// Three players take turns until six moves have been made.
+    struct RotatingGame;
+
+    #[derive(Clone, Default)]
+    struct RotatingState {
+        moves: usize,
+    }
+
+    #[derive(Copy, Clone, PartialEq)]
+    struct Pass;
+
+    #[derive(Copy, Clone)]
+    struct NoWinner;
+
+    impl GameAction for Pass {}
+
+    impl GameOutcome for NoWinner {
+        fn winner(&self) -> Option<PlayerId> {
+            None
+        }
+    }
+
+    impl GameState for RotatingState {
+        type Action = Pass;
+        type Outcome = NoWinner;
+
+        fn make_next(&mut self, _: Self::Action) {
+            self.moves += 1;
+        }
+
+        fn legal_actions(&self) -> Vec<Self::Action> {
+            vec![Pass]
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerId(self.moves % 3)
+        }
+
+        fn outcome(&self) -> Option<Self::Outcome> {
+            if self.moves >= 6 {
+                Some(NoWinner)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl Game for RotatingGame {
+        type GameState = RotatingState;
+        type GameAction = Pass;
+        type GameOutcome = NoWinner;
+    }
+
+    /// Logs its own id, and whose turn it was, every time it is asked to move.
+    struct LoggingAgent {
+        id: usize,
+        log: Rc<RefCell<Vec<(usize, PlayerId)>>>,
+    }
+
+    impl GameAgent<RotatingGame> for LoggingAgent {
+        fn pick_action(&self, state: &RotatingState, actions: &[Pass]) -> Pass {
+            self.log
+                .borrow_mut()
+                .push((self.id, state.current_player_turn()));
+            actions[0]
+        }
+    }
+
+    #[test]
+    fn runner_consults_each_of_three_players_on_their_turn() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let agents = (0..3)
+            .map(|id| {
+                Box::new(LoggingAgent {
+                    id,
+                    log: Rc::clone(&log),
+                }) as Box<dyn GameAgent<RotatingGame>>
+            })
+            .collect();
+
+        GameRunner::new(agents, RotatingState::default()).play();
+
+        let log = log.borrow();
+        let order: Vec<usize> = log.iter().map(|&(id, _)| id).collect();
+        assert_eq!(vec![0, 1, 2, 0, 1, 2], order);
+        assert!(log.iter().all(|&(id, turn)| turn == PlayerId(id)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn runner_panics_without_an_agent_for_the_player_to_move() {
+        let agents: Vec<Box<dyn GameAgent<RotatingGame>>> = Vec::new();
+
+        GameRunner::new(agents, RotatingState::default()).play();
+    }
+
+    #[test]
+    fn player_colors_are_the_first_two_ids() {
+        assert_eq!(PlayerId(0), PlayerColor::Black.into());
+        assert_eq!(PlayerId(1), PlayerColor::White.into());
+        assert_eq!(PlayerColor::White, PlayerColor::Black.opponent());
+    }
+
+    #[test]
+    fn transposed_move_orders_hash_equally() {
+        let start = SimpleGameState::new();
+
+        let two_then_three = start
+            .next(SimpleGameAction::new(2))
+            .next(SimpleGameAction::new(3));
+        let three_then_two = start
+            .next(SimpleGameAction::new(3))
+            .next(SimpleGameAction::new(2));
+        let four_then_two = start
+            .next(SimpleGameAction::new(4))
+            .next(SimpleGameAction::new(2));
+
+        assert_eq!(two_then_three.zobrist_hash(), three_then_two.zobrist_hash());
+        assert_ne!(two_then_three.zobrist_hash(), four_then_two.zobrist_hash());
+    }
+
+    #[test]
+    fn draw_has_no_winner() {
+        let drawn = DrawState::default().next(DrawAction::Draw);
+        let won = DrawState::default().next(DrawAction::Win);
+
+        assert_eq!(None, drawn.outcome().unwrap().winner());
+        assert_eq!(Some(PlayerId(0)), won.outcome().unwrap().winner());
+    }
+
+    #[test]
+    fn is_terminal_follows_outcome() {
+        let start = DrawState::default();
+
+        assert!(!start.is_terminal());
+        assert!(start.next(DrawAction::Draw).is_terminal());
+    }
 }
@@ -0,0 +1,4012 @@
+use crate::{Game, GameAgent, GameOutcome, GameState, HashableState, Indexable, PlayerId};
+use rand::distributions::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::Gamma;
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tree::node::{Node, NodePool};
+
+/// A `tracing::trace!` event with the `trace` feature, and nothing at all without it,
+/// so the arguments are not even evaluated.
+#[cfg(feature = "trace")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        tracing::trace!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {};
+}
+
+mod builder;
+mod evaluator;
+mod rollout;
+mod selection;
+mod transposition;
+pub mod virtual_loss;
+
+pub use builder::MctsBuilder;
+pub use evaluator::{Evaluation, PolicyValueNet, StateEvaluator};
+pub use rollout::{DecisiveMoveRollout, DepthLimitedRollout, RandomRollout, RolloutPolicy};
+pub use selection::{PuctPolicy, SelectionPolicy, Ucb1Policy, Ucb1TunedPolicy};
+pub use transposition::TranspositionTable;
+
+/// Whether the game-theoretic value of a node has been proven by the solver,
+/// from the perspective of the player who took the action leading into it
+/// (for a fresh root, which has no such action, the player to move).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProofStatus {
+    Unknown,
+    ProvenWin,
+    ProvenLoss,
+}
+
+/// The visit and reward statistics of a game state.
+/// These are shared between every node reaching the same state
+/// when a transposition table is in use.
+#[derive(Default)]
+pub(crate) struct NodeStats {
+    visits: Cell<u32>,
+    wins: Cell<u32>,
+    reward_sum: Cell<f64>,
+    reward_sq_sum: Cell<f64>,
+    /// Every player's reward summed over the visits, indexed by `PlayerId`.
+    player_reward_sums: RefCell<Vec<f64>>,
+}
+
+impl NodeStats {
+    /// Records a visit worth `reward * discount`, still counting it as a win
+    /// if the undiscounted `reward` was one.
+    fn record(&self, reward: f64, discount: f64) {
+        self.visits.set(self.visits.get() + 1);
+        if reward >= 1.0 {
+            self.wins.set(self.wins.get() + 1);
+        }
+
+        let reward = reward * discount;
+        self.reward_sum.set(self.reward_sum.get() + reward);
+        self.reward_sq_sum
+            .set(self.reward_sq_sum.get() + reward * reward);
+    }
+
+    fn record_player_rewards(&self, rewards: &[f64], discount: f64) {
+        let mut sums = self.player_reward_sums.borrow_mut();
+        if sums.len() < rewards.len() {
+            sums.resize(rewards.len(), 0.0);
+        }
+
+        for (sum, reward) in sums.iter_mut().zip(rewards) {
+            *sum += reward * discount;
+        }
+    }
+
+    /// Counts a visit that has not been scored yet, as if it were a loss,
+    /// so that selection avoids the node until `revert_virtual_loss`.
+    fn apply_virtual_loss(&self) {
+        self.visits.set(self.visits.get() + 1);
+    }
+
+    fn revert_virtual_loss(&self) {
+        self.visits.set(self.visits.get() - 1);
+    }
+}
+
+/// Whether a player chooses among a node's children, or chance does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Decision,
+    /// The children are chance events, sampled in proportion to
+    /// the probabilities stored as their priors.
+    Chance,
+}
+
+/// The statistics kept for each node of the search tree.
+/// Rewards are from the perspective of the player who took
+/// the action leading into the node.
+pub struct NodeData<G: Game> {
+    action: Option<G::GameAction>,
+    player: Option<PlayerId>,
+    prior: Cell<f64>,
+    stats: Rc<NodeStats>,
+    proof: Cell<ProofStatus>,
+    kind: Cell<NodeKind>,
+    amaf_visits: Cell<u32>,
+    amaf_reward_sum: Cell<f64>,
+    /// With progressive widening, the actions not yet given children,
+    /// with their priors, so that the next to add is at the end.
+    untried: RefCell<Vec<(G::GameAction, f64)>>,
+}
+
+impl<G: Game> NodeData<G> {
+    fn root() -> Self {
+        Self::with_prior(None, None, 0.0)
+    }
+
+    fn with_prior(action: Option<G::GameAction>, player: Option<PlayerId>, prior: f64) -> Self {
+        Self::with_stats(action, player, prior, Rc::default())
+    }
+
+    fn with_stats(
+        action: Option<G::GameAction>,
+        player: Option<PlayerId>,
+        prior: f64,
+        stats: Rc<NodeStats>,
+    ) -> Self {
+        Self {
+            action,
+            player,
+            prior: Cell::new(prior),
+            stats,
+            proof: Cell::new(ProofStatus::Unknown),
+            kind: Cell::new(NodeKind::Decision),
+            amaf_visits: Cell::new(0),
+            amaf_reward_sum: Cell::new(0.0),
+            untried: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The action leading into this node, or `None` for the root.
+    pub fn action(&self) -> Option<G::GameAction> {
+        self.action
+    }
+
+    /// The player who took `action`, or `None` for the root.
+    pub fn player(&self) -> Option<PlayerId> {
+        self.player
+    }
+
+    /// The policy's prior probability of `action`,
+    /// or 0 if the node was expanded without priors.
+    pub fn prior(&self) -> f64 {
+        self.prior.get()
+    }
+
+    /// With a transposition table, this counts visits to the node's state
+    /// through any path, not just through this node.
+    pub fn visits(&self) -> u32 {
+        self.stats.visits.get()
+    }
+
+    /// The number of visits whose reward was at least 1, i.e. a win.
+    pub fn wins(&self) -> u32 {
+        self.stats.wins.get()
+    }
+
+    /// The sum of the rewards of `player`, the player who moved into this node.
+    /// This is the entry of `reward_sum_for` that selection maximizes, since `player`
+    /// is the one choosing among the parent's children.
+    pub fn reward_sum(&self) -> f64 {
+        self.stats.reward_sum.get()
+    }
+
+    /// The sum of `player`'s rewards over the visits to this node,
+    /// whoever moved into it, so that with three or more players
+    /// each one's results through the node are tracked independently.
+    pub fn reward_sum_for(&self, player: PlayerId) -> f64 {
+        self.stats
+            .player_reward_sums
+            .borrow()
+            .get(player.0)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// The average of `player`'s rewards per visit, or 0 if never visited.
+    pub fn mean_reward_for(&self, player: PlayerId) -> f64 {
+        match self.visits() {
+            0 => 0.0,
+            visits => self.reward_sum_for(player) / f64::from(visits),
+        }
+    }
+
+    /// The sum of each visit's reward squared, from which UCB1-Tuned
+    /// estimates the variance of the rewards.
+    pub fn reward_sq_sum(&self) -> f64 {
+        self.stats.reward_sq_sum.get()
+    }
+
+    /// The average reward per visit, or 0 if never visited.
+    pub fn mean_reward(&self) -> f64 {
+        match self.visits() {
+            0 => 0.0,
+            visits => self.reward_sum() / f64::from(visits),
+        }
+    }
+
+    /// The fraction of visits that were wins, or 0 if never visited.
+    pub fn win_rate(&self) -> f64 {
+        match self.visits() {
+            0 => 0.0,
+            visits => f64::from(self.wins()) / f64::from(visits),
+        }
+    }
+
+    pub fn proof_status(&self) -> ProofStatus {
+        self.proof.get()
+    }
+
+    /// Whether the node's children are chosen by a player or by chance,
+    /// which is only known once it has been expanded.
+    pub fn kind(&self) -> NodeKind {
+        self.kind.get()
+    }
+
+    /// The number of iterations in which `action` was played by `player`
+    /// anywhere below the parent, in the tree or in the rollout ("all moves as first").
+    /// Only tracked when RAVE is enabled.
+    pub fn amaf_visits(&self) -> u32 {
+        self.amaf_visits.get()
+    }
+
+    pub fn amaf_reward_sum(&self) -> f64 {
+        self.amaf_reward_sum.get()
+    }
+
+    /// The average AMAF reward, or 0 if there are no AMAF visits.
+    pub fn amaf_mean_reward(&self) -> f64 {
+        match self.amaf_visits() {
+            0 => 0.0,
+            visits => self.amaf_reward_sum() / f64::from(visits),
+        }
+    }
+
+    fn record_amaf(&self, reward: f64) {
+        self.amaf_visits.set(self.amaf_visits.get() + 1);
+        self.amaf_reward_sum
+            .set(self.amaf_reward_sum.get() + reward);
+    }
+
+    fn is_proven(&self) -> bool {
+        self.proof_status() != ProofStatus::Unknown
+    }
+
+    #[cfg(test)]
+    fn record(&self, reward: f64) {
+        self.stats.record(reward, 1.0);
+    }
+
+    /// Records a visit scored `rewards[p] * discount` for each player `p`,
+    /// counting the mover's entry as the node's own reward.
+    fn record_rewards(&self, rewards: &[f64], discount: f64) {
+        let reward = self.player().map_or(0.0, |p| rewards[p.0]);
+        self.stats.record(reward, discount);
+        self.stats.record_player_rewards(rewards, discount);
+    }
+}
+
+/// The UCB1 score of a child: its mean reward plus an exploration bonus
+/// that shrinks the more often it is visited relative to its parent.
+/// An unvisited child scores infinity, so it is always explored first.
+pub fn ucb1(child_reward_sum: f64, child_visits: u32, parent_visits: u32, c: f64) -> f64 {
+    if child_visits == 0 {
+        return f64::INFINITY;
+    }
+
+    let child_visits = f64::from(child_visits);
+    let mean = child_reward_sum / child_visits;
+    let exploration = (f64::from(parent_visits).ln() / child_visits).sqrt();
+
+    mean + c * exploration
+}
+
+/// The UCB1-Tuned score of a child, which scales UCB1's exploration bonus
+/// by an upper bound on the variance of its rewards, capped at 1/4,
+/// the largest variance possible for rewards between 0 and 1:
+/// `mean + sqrt(ln(parent_visits) / visits * min(1/4, V))`, where
+/// `V = variance + sqrt(2 * ln(parent_visits) / visits)`.
+/// An unvisited child scores infinity, so it is always explored first.
+pub fn ucb1_tuned(reward_sum: f64, reward_sq_sum: f64, visits: u32, parent_visits: u32) -> f64 {
+    if visits == 0 {
+        return f64::INFINITY;
+    }
+
+    let visits = f64::from(visits);
+    let log_parent = f64::from(parent_visits).ln();
+    let mean = reward_sum / visits;
+    let variance = reward_sq_sum / visits - mean * mean;
+    let variance_bound = variance + (2.0 * log_parent / visits).sqrt();
+
+    mean + (log_parent / visits * variance_bound.min(0.25)).sqrt()
+}
+
+/// The PUCT score of a child, as used in AlphaZero-style search:
+/// `Q + c_puct * P * sqrt(parent_visits) / (1 + child_visits)`,
+/// where `Q` is the child's mean reward and `P` its prior probability.
+pub fn puct(
+    mean_reward: f64,
+    prior: f64,
+    child_visits: u32,
+    parent_visits: u32,
+    c_puct: f64,
+) -> f64 {
+    let exploration = f64::from(parent_visits).sqrt() / (1.0 + f64::from(child_visits));
+
+    mean_reward + c_puct * prior * exploration
+}
+
+/// The weight RAVE gives a child's AMAF value over its own mean reward:
+/// `amaf_visits / (amaf_visits + visits + bias * amaf_visits * visits)`.
+/// This starts at 1 and falls towards 0 as the child's own visits accumulate,
+/// faster for a larger `bias`.
+pub fn rave_beta(child_visits: u32, amaf_visits: u32, bias: f64) -> f64 {
+    if amaf_visits == 0 {
+        return 0.0;
+    }
+
+    let visits = f64::from(child_visits);
+    let amaf_visits = f64::from(amaf_visits);
+
+    amaf_visits / (amaf_visits + visits + bias * amaf_visits * visits)
+}
+
+/// Converts a game's outcome into the numeric reward that search backpropagates,
+/// so the same search serves win/loss/draw results, scores, or any other outcome.
+/// The reward is from `perspective`'s point of view, where higher is better for them,
+/// so each node on a path can be credited for the player who moved into it.
+/// Implemented for any `Fn(&G::GameOutcome, PlayerId) -> f64`, such as `winner_reward`.
+pub trait Reward<G: Game> {
+    fn reward(&self, outcome: &G::GameOutcome, perspective: PlayerId) -> f64;
+}
+
+impl<G: Game, F: Fn(&G::GameOutcome, PlayerId) -> f64> Reward<G> for F {
+    fn reward(&self, outcome: &G::GameOutcome, perspective: PlayerId) -> f64 {
+        self(outcome, perspective)
+    }
+}
+
+/// Scores an outcome by `GameOutcome::value_for`, which unless overridden is
+/// 1 if `player` won, 0 if another player did, and 0.5 if nobody did.
+pub fn winner_reward<O: GameOutcome>(outcome: &O, player: PlayerId) -> f64 {
+    outcome.value_for(player)
+}
+
+/// Expands `node`, reached in `state`, with one child per action
+/// in `priors`, each holding its prior probability from a policy evaluator.
+pub fn expand_with_priors<G: Game>(
+    node: &mut Node<NodeData<G>>,
+    state: &G::GameState,
+    priors: Vec<(G::GameAction, f64)>,
+) {
+    let player = state.current_player_turn();
+
+    node.add_all_children(
+        priors
+            .into_iter()
+            .map(|(action, prior)| NodeData::with_prior(Some(action), Some(player), prior)),
+    );
+}
+
+/// The actions leading down `path`, each with the player who took it.
+fn path_actions<G: Game>(path: &[Node<NodeData<G>>]) -> Vec<(PlayerId, G::GameAction)> {
+    path[1..]
+        .iter()
+        .map(|n| (n.data().player().unwrap(), n.data().action().unwrap()))
+        .collect()
+}
+
+/// Search statistics for one of the root's children.
+pub struct ChildStat<G: Game> {
+    pub action: G::GameAction,
+    pub visits: u32,
+    pub mean_reward: f64,
+    pub win_rate: f64,
+}
+
+/// The evaluation of one candidate move, as reported by `Mcts::analyze`.
+pub struct MoveAnalysis<G: Game> {
+    pub action: G::GameAction,
+    pub visits: u32,
+    pub win_rate: f64,
+    pub mean_value: f64,
+}
+
+/// A snapshot of a search in progress, as passed to the `Mcts::with_progress` callback.
+pub struct SearchProgress<G: Game> {
+    /// How many iterations this search has run so far.
+    pub iterations_done: usize,
+    /// The most-visited root action, or `None` before the root is expanded.
+    pub best_action: Option<G::GameAction>,
+    /// The win rate of `best_action`, or 0 without one.
+    pub best_win_rate: f64,
+    /// The number of nodes in the tree, including the root.
+    pub nodes_in_tree: usize,
+}
+
+/// One position from a self-play game, for training policy and value networks.
+pub struct TrainingSample<G: Game> {
+    /// The position searched.
+    pub state: G::GameState,
+    /// Each root action with its share of the root's visits, summing to 1.
+    pub policy: Vec<(G::GameAction, f64)>,
+    /// The action played from `state`.
+    pub action: G::GameAction,
+    /// How the game eventually ended.
+    pub outcome: G::GameOutcome,
+}
+
+/// How the final move is picked from the root's children once search is done.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FinalMoveSelection {
+    /// The most-visited child. This is the standard, safe default,
+    /// since visit counts are far less noisy than value estimates.
+    MaxVisits,
+    /// The child with the highest mean reward.
+    MaxValue,
+    /// The most-visited child, breaking ties by mean reward.
+    Robust,
+}
+
+/// The earliest item with the greatest key.
+fn first_max_by<T, K: PartialOrd>(
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> K,
+) -> Option<T> {
+    let mut best: Option<(K, T)> = None;
+
+    for item in items {
+        let item_key = key(&item);
+
+        if best
+            .as_ref()
+            .is_none_or(|(best_key, _)| item_key > *best_key)
+        {
+            best = Some((item_key, item));
+        }
+    }
+
+    best.map(|(_, item)| item)
+}
+
+type RewardFn<G> = Arc<dyn Reward<G> + Send + Sync>;
+type SharedRollout<G> = Arc<dyn RolloutPolicy<G> + Send + Sync>;
+type SharedSelection<G> = Arc<dyn SelectionPolicy<G> + Send + Sync>;
+type SharedEvaluator<G> = Arc<dyn StateEvaluator<G> + Send + Sync>;
+type SharedNet<G> = Arc<dyn PolicyValueNet<G> + Send + Sync>;
+type SharedAgent<G> = Arc<dyn GameAgent<G> + Send + Sync>;
+type StateHash<G> = fn(&<G as Game>::GameState) -> u64;
+type ProgressFn<G> = Box<dyn FnMut(SearchProgress<G>)>;
+
+/// Monte Carlo Tree Search over a `Game`.
+pub struct Mcts<G: Game> {
+    reward: RewardFn<G>,
+    rollout: SharedRollout<G>,
+    selection: Option<SharedSelection<G>>,
+    exploration: f64,
+    rave_bias: Option<f64>,
+    fpu: f64,
+    transpositions: Option<TranspositionTable<G::GameState>>,
+    seed: Option<u64>,
+    rng: StdRng,
+    root: Option<Node<NodeData<G>>>,
+    reuse_root: bool,
+    evaluator: Option<SharedEvaluator<G>>,
+    net: Option<SharedNet<G>>,
+    batch_size: usize,
+    widening: Option<Widening>,
+    discount: f64,
+    player_count: usize,
+    early_stop: Option<usize>,
+    max_nodes: Option<usize>,
+    opponent: Option<SharedAgent<G>>,
+    reward_bounds: Option<(f64, f64)>,
+    /// The number of nodes in the tree being searched, kept under `max_nodes`.
+    node_count: Cell<usize>,
+    /// The nodes pruned to stay under `max_nodes`, for reuse by later expansions.
+    pool: RefCell<NodePool<NodeData<G>>>,
+    /// The progress callback, with how many iterations apart it is invoked.
+    progress: Option<(usize, ProgressFn<G>)>,
+    /// Scratch space for the legal actions of each expanded node.
+    actions: Vec<G::GameAction>,
+}
+
+/// The result of the simulation phase, from which each player's reward is derived.
+enum Simulation<O> {
+    /// The outcome of a game played out to the end.
+    Outcome(O),
+    /// The value of the leaf to `player`, the player to move there, from a `StateEvaluator`.
+    Evaluated { player: PlayerId, value: f64 },
+}
+
+/// Everything a parallel worker needs to build its own `Mcts`
+/// with the same settings, which unlike the `Mcts` itself
+/// can be shared across threads.
+struct SearchConfig<G: Game> {
+    reward: RewardFn<G>,
+    rollout: SharedRollout<G>,
+    selection: Option<SharedSelection<G>>,
+    exploration: f64,
+    rave_bias: Option<f64>,
+    fpu: f64,
+    transposition_hash: Option<StateHash<G>>,
+    evaluator: Option<SharedEvaluator<G>>,
+    net: Option<SharedNet<G>>,
+    batch_size: usize,
+    widening: Option<Widening>,
+    discount: f64,
+    player_count: usize,
+    early_stop: Option<usize>,
+    max_nodes: Option<usize>,
+    opponent: Option<SharedAgent<G>>,
+    reward_bounds: Option<(f64, f64)>,
+}
+
+/// How nodes expanded one child at a time may gain more.
+#[derive(Copy, Clone)]
+enum Widening {
+    /// Progressive widening's limit of `ceil(c * visits ^ alpha)` children per node.
+    Progressive { c: f64, alpha: f64 },
+    /// One more child on every visit, until no untried actions remain.
+    OnePerVisit,
+}
+
+impl Widening {
+    /// How many children a node with `visits` visits may have, always at least one.
+    fn max_children(&self, visits: u32) -> usize {
+        match *self {
+            Widening::Progressive { c, alpha } => {
+                ((c * (visits as f64).powf(alpha)).ceil() as usize).max(1)
+            }
+            Widening::OnePerVisit => usize::MAX,
+        }
+    }
+}
+
+impl<G: Game> SearchConfig<G> {
+    fn build(&self, seed: u64) -> Mcts<G> {
+        Mcts {
+            reward: Arc::clone(&self.reward),
+            rollout: Arc::clone(&self.rollout),
+            selection: self.selection.clone(),
+            exploration: self.exploration,
+            rave_bias: self.rave_bias,
+            fpu: self.fpu,
+            transpositions: self.transposition_hash.map(TranspositionTable::with_hash),
+            evaluator: self.evaluator.clone(),
+            net: self.net.clone(),
+            batch_size: self.batch_size,
+            widening: self.widening,
+            discount: self.discount,
+            player_count: self.player_count,
+            early_stop: self.early_stop,
+            max_nodes: self.max_nodes,
+            opponent: self.opponent.clone(),
+            reward_bounds: self.reward_bounds,
+            node_count: Cell::new(0),
+            pool: RefCell::default(),
+            progress: None,
+            seed: Some(seed),
+            rng: StdRng::seed_from_u64(seed),
+            root: None,
+            reuse_root: false,
+            actions: Vec::new(),
+        }
+    }
+}
+
+impl<G: Game> Mcts<G>
+where
+    G::GameState: HashableState,
+{
+    /// Enables a transposition table, so that when a state already in the tree
+    /// is reached again by a different sequence of actions, its node shares
+    /// the visit and reward statistics gathered for it elsewhere.
+    /// States are matched by `HashableState::zobrist_hash`.
+    /// This assumes the same player always moves into a given state,
+    /// since the shared rewards are from that player's perspective.
+    pub fn with_transpositions(mut self) -> Self {
+        self.transpositions = Some(TranspositionTable::new());
+        self
+    }
+}
+
+impl<G: Game> Mcts<G>
+where
+    G::GameAction: Indexable,
+{
+    /// The visits to each root action from the most recent search,
+    /// at the action's `Indexable::to_index`, and zero for actions not at the root.
+    /// All zeros if there has been no search.
+    pub fn dense_root_visits(&self) -> Vec<u32> {
+        let mut visits = vec![0; G::GameAction::MAX_ACTIONS];
+
+        for stat in self.root_statistics() {
+            visits[stat.action.to_index()] = stat.visits;
+        }
+
+        visits
+    }
+}
+
+/// Searches with `winner_reward`, which unless `GameOutcome::value_for` is overridden
+/// counts a draw as half a win.
+impl<G: Game> Default for Mcts<G> {
+    fn default() -> Self {
+        Self::new(|outcome: &G::GameOutcome, player| winner_reward(outcome, player))
+    }
+}
+
+impl<G: Game> Mcts<G> {
+    /// `reward` scores a finished game's outcome from a given player's perspective,
+    /// where higher is better for that player (e.g. 1 for a win, 0 for a loss).
+    /// A reward of 1 or more is counted as a win.
+    pub fn new(reward: impl Reward<G> + Send + Sync + 'static) -> Self {
+        Self::with_reward_fn(Arc::new(reward))
+    }
+
+    /// Collects the settings in an `MctsBuilder` instead.
+    pub fn builder() -> MctsBuilder<G> {
+        MctsBuilder::new()
+    }
+
+    fn with_reward_fn(reward: RewardFn<G>) -> Self {
+        Self {
+            reward,
+            rollout: Arc::new(RandomRollout),
+            selection: None,
+            exploration: std::f64::consts::SQRT_2,
+            rave_bias: None,
+            fpu: f64::INFINITY,
+            transpositions: None,
+            seed: None,
+            rng: StdRng::from_entropy(),
+            root: None,
+            reuse_root: false,
+            evaluator: None,
+            net: None,
+            batch_size: 1,
+            widening: None,
+            discount: 1.0,
+            player_count: 2,
+            early_stop: None,
+            max_nodes: None,
+            opponent: None,
+            reward_bounds: None,
+            node_count: Cell::new(0),
+            pool: RefCell::default(),
+            progress: None,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Sets the exploration constant `c` of the default `Ucb1Policy`, which defaults to sqrt(2).
+    pub fn with_exploration(mut self, c: f64) -> Self {
+        self.exploration = c;
+        self
+    }
+
+    /// Selects children by PUCT with the exploration constant `c_puct`,
+    /// as in AlphaZero-style search, replacing the selection policy.
+    pub fn with_c_puct(self, c_puct: f64) -> Self {
+        self.with_selection_policy(Box::new(PuctPolicy::new(c_puct)))
+    }
+
+    /// Enables RAVE, which tracks AMAF statistics and, with the default `Ucb1Policy`,
+    /// blends them into selection with the weight given by `rave_beta` for this `bias`.
+    /// AMAF statistics only include rollout actions if the rollout policy records them.
+    pub fn with_rave(mut self, bias: f64) -> Self {
+        self.rave_bias = Some(bias);
+        self
+    }
+
+    /// Sets the first-play urgency of the default `Ucb1Policy`: the score assumed
+    /// for an unvisited child. This defaults to infinity, the classic rule
+    /// of trying every child once before revisiting any.
+    pub fn with_fpu(mut self, fpu: f64) -> Self {
+        self.fpu = fpu;
+        self
+    }
+
+    /// The transposition table shared by this searcher's nodes, if enabled.
+    pub fn transpositions(&self) -> Option<&TranspositionTable<G::GameState>> {
+        self.transpositions.as_ref()
+    }
+
+    /// Seeds the random number generator behind all of the search's randomness
+    /// (rollouts, root noise and action sampling), which is otherwise seeded from entropy.
+    /// Two searchers with the same seed and settings build identical trees
+    /// from the same state and iteration count.
+    /// `search_parallel` derives each worker's seed from this one.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Replaces the default `RandomRollout` used in the simulation phase.
+    pub fn with_rollout_policy(mut self, rollout: Box<dyn RolloutPolicy<G> + Send + Sync>) -> Self {
+        self.rollout = Arc::from(rollout);
+        self
+    }
+
+    /// Caps rollouts at `max_depth` random moves, with `evaluator` estimating
+    /// the outcome of any state reached without the game ending,
+    /// by switching to a `DepthLimitedRollout`. Rollouts are unbounded by default.
+    pub fn with_max_rollout_depth(
+        self,
+        max_depth: usize,
+        evaluator: impl Fn(&G::GameState) -> G::GameOutcome + Send + Sync + 'static,
+    ) -> Self
+    where
+        G: 'static,
+    {
+        self.with_rollout_policy(Box::new(DepthLimitedRollout::new(max_depth, evaluator)))
+    }
+
+    /// Evaluates each newly-reached, non-terminal leaf with `evaluator`
+    /// instead of simulating from it with the rollout policy.
+    /// Players other than the one to move at the leaf are credited with
+    /// one minus its value, assuming a two-player, zero-sum game.
+    pub fn with_evaluator(mut self, evaluator: Box<dyn StateEvaluator<G> + Send + Sync>) -> Self {
+        self.evaluator = Some(Arc::from(evaluator));
+        self
+    }
+
+    /// Searches AlphaZero-style: every newly-reached, non-terminal leaf is
+    /// expanded at once with `net`'s priors and scored with its value,
+    /// replacing rollouts and any `StateEvaluator`.
+    /// Installs a default `PuctPolicy` to select by those priors,
+    /// unless a selection policy has already been set.
+    pub fn with_policy_value_net(mut self, net: Box<dyn PolicyValueNet<G> + Send + Sync>) -> Self {
+        self.net = Some(Arc::from(net));
+        self.selection
+            .get_or_insert_with(|| Arc::new(PuctPolicy::default()));
+        self
+    }
+
+    /// Sets how many leaves `search_batched` gathers for each call to
+    /// `PolicyValueNet::evaluate_batch`, which defaults to 1.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Enables progressive widening, for games with too many actions to try them all:
+    /// a node with `n` visits may only have `ceil(c * n ^ alpha)` children.
+    /// Expansion adds just one child, and each time a node's visits allow
+    /// another, the next is added, in descending order of prior
+    /// (in the order of `legal_actions` for actions without priors).
+    pub fn with_progressive_widening(mut self, c: f64, alpha: f64) -> Self {
+        self.widening = Some(Widening::Progressive { c, alpha });
+        self
+    }
+
+    /// Classic UCT expansion: expanding a node adds just one child, and every later
+    /// visit through it adds one more previously untried action, ending the descent
+    /// at the new child, until all of its actions have children.
+    /// Actions are tried in the same order as with progressive widening,
+    /// which this replaces.
+    pub fn with_incremental_expansion(mut self) -> Self {
+        self.widening = Some(Widening::OnePerVisit);
+        self
+    }
+
+    /// Sets the factor applied to a reward for each step it is backpropagated
+    /// above the leaf, so a node `d` steps above it is credited `reward * discount ^ d`,
+    /// favoring quicker wins. The default of 1.0 reproduces undiscounted backpropagation exactly.
+    pub fn with_discount(mut self, discount: f64) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    /// Sets how many players the game has, so that every node tracks each one's reward
+    /// (see `NodeData::reward_sum_for`). The default of 2 suits two-player games;
+    /// a player beyond the count is still tracked once it moves on a backpropagated path.
+    pub fn with_player_count(mut self, players: usize) -> Self {
+        self.player_count = players;
+        self
+    }
+
+    /// Lets `search` and `search_ismcts` stop before their iteration budget is spent:
+    /// every `every` iterations, if the most-visited root child leads the next
+    /// by more visits than there are iterations remaining, no outcome of those
+    /// iterations could change the choice, so the search returns at once.
+    pub fn with_early_stop(mut self, every: usize) -> Self {
+        self.early_stop = Some(every.max(1));
+        self
+    }
+
+    /// Caps the tree at `max_nodes` nodes, including the root.
+    /// When an expansion would exceed the cap, the least-visited nodes whose
+    /// children are all leaves lose those children first, becoming leaves again
+    /// with their statistics kept, until the tree is back under 90% of the cap,
+    /// so that pruning happens once per batch of expansions rather than on each one.
+    /// Nodes on the path being expanded are never pruned, and if nothing else can be
+    /// and the new children still do not fit, the leaf is simulated from without being expanded.
+    /// Pruned nodes are kept in a `NodePool` and reused by later expansions,
+    /// so a long capped search stops allocating once the tree is full.
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Declares that the reward function returns values between `min` and `max`,
+    /// so each reward is rescaled into [0, 1] before it is backpropagated
+    /// or used to prove a node, with values outside the bounds clamped.
+    /// The exploration constant, and the proof thresholds of 0 and 1, assume
+    /// rewards in [0, 1]; with rewards of any other scale and no bounds,
+    /// the constant no longer balances exploration against exploitation.
+    /// Values from a `StateEvaluator` or `PolicyValueNet` are already in [0, 1]
+    /// and are not rescaled.
+    pub fn with_reward_bounds(mut self, min: f64, max: f64) -> Self {
+        self.reward_bounds = Some((min, max));
+        self
+    }
+
+    /// Models every player other than the one to move at the root as `opponent`,
+    /// rather than assuming they search as well as this searcher does,
+    /// so the chosen move exploits what is known of a weaker opponent.
+    /// On their plies, selection follows the opponent's pick wherever it has a child,
+    /// and rollouts play their pick, while the searching player's plies
+    /// are selected as usual and played by `RolloutPolicy::choose_action` in rollouts,
+    /// which still end wherever the policy's `cutoff` ends them.
+    /// Nodes where the opponent moves are never proven, since proofs assume perfect play.
+    pub fn with_opponent_model(mut self, opponent: Box<dyn GameAgent<G> + Send + Sync>) -> Self {
+        self.opponent = Some(Arc::from(opponent));
+        self
+    }
+
+    /// Invokes `callback` with a `SearchProgress` every `every` iterations of
+    /// `search`, `search_for` and `search_ismcts`, and once more after the last
+    /// iteration of `search` and `search_ismcts` if it did not fall on a multiple.
+    /// This is meant for live "thinking" displays; the callback is not passed
+    /// to the workers of `search_parallel`.
+    pub fn with_progress(
+        mut self,
+        every: usize,
+        callback: Box<dyn FnMut(SearchProgress<G>)>,
+    ) -> Self {
+        self.progress = Some((every.max(1), callback));
+        self
+    }
+
+    /// Replaces the default `Ucb1Policy` used in the selection phase.
+    /// Proven-winning children are still always selected, whatever the policy.
+    pub fn with_selection_policy(
+        mut self,
+        selection: Box<dyn SelectionPolicy<G> + Send + Sync>,
+    ) -> Self {
+        self.selection = Some(Arc::from(selection));
+        self
+    }
+
+    fn config(&self) -> SearchConfig<G> {
+        SearchConfig {
+            reward: Arc::clone(&self.reward),
+            rollout: Arc::clone(&self.rollout),
+            selection: self.selection.clone(),
+            exploration: self.exploration,
+            rave_bias: self.rave_bias,
+            fpu: self.fpu,
+            transposition_hash: self.transpositions.as_ref().map(|t| t.hash_fn()),
+            evaluator: self.evaluator.clone(),
+            net: self.net.clone(),
+            batch_size: self.batch_size,
+            widening: self.widening,
+            discount: self.discount,
+            player_count: self.player_count,
+            early_stop: self.early_stop,
+            max_nodes: self.max_nodes,
+            opponent: self.opponent.clone(),
+            reward_bounds: self.reward_bounds,
+        }
+    }
+
+    /// The seed of each of `threads` parallel workers: distinct from one another,
+    /// and fixed if this searcher was seeded.
+    fn worker_seeds(&self, threads: usize) -> Vec<u64> {
+        let base = self.seed.unwrap_or_else(rand::random);
+
+        (1..=threads as u64).map(|i| base.wrapping_add(i)).collect()
+    }
+
+    /// Searches from `root_state` for `iterations` rounds of
+    /// selection, expansion, simulation and backpropagation,
+    /// then returns the most-visited action from the root.
+    /// Panics if `root_state` has no legal actions.
+    pub fn search(&mut self, root_state: G::GameState, iterations: usize) -> G::GameAction {
+        let root = self.take_root();
+
+        for done in 1..=iterations {
+            self.iterate(&root, &root_state);
+
+            let decided = self.is_decided(&root, done, iterations);
+            self.report_progress(&root, done, decided || done == iterations);
+            if decided {
+                break;
+            }
+        }
+
+        self.finish_search(root)
+    }
+
+    /// Like `search`, but gathers up to the configured batch size of leaves
+    /// before scoring them all with one `PolicyValueNet::evaluate_batch` call.
+    /// Each gathered leaf's path takes a virtual loss until it is scored,
+    /// steering the selections within a batch apart; if selection still
+    /// returns to a gathered leaf, the batch is evaluated early.
+    /// Terminal leaves are scored by their outcome as soon as they are reached.
+    /// Panics without a policy-value net, or if `root_state` has no legal actions.
+    pub fn search_batched(&mut self, root_state: G::GameState, iterations: usize) -> G::GameAction {
+        let net = Arc::clone(
+            self.net
+                .as_ref()
+                .expect("Batched search needs a policy-value net."),
+        );
+        let root = self.take_root();
+        let root_player = root_state.current_player_turn();
+        let mut done = 0;
+
+        while done < iterations {
+            let mut pending: Vec<Vec<Node<NodeData<G>>>> = Vec::new();
+            let mut states = Vec::new();
+
+            while pending.len() < self.batch_size && done + pending.len() < iterations {
+                let (path, state) = self.select_leaf(&root, &root_state);
+                let leaf = path.last().unwrap();
+
+                if let Some(outcome) = state.outcome() {
+                    self.prove(&path, &outcome, root_player);
+                    self.backpropagate(&path, &Simulation::Outcome(outcome));
+                    done += 1;
+                    continue;
+                }
+
+                if pending.iter().any(|p| p.last().unwrap().ptr_eq(leaf)) {
+                    break;
+                }
+
+                path.iter()
+                    .for_each(|n| n.data().stats.apply_virtual_loss());
+                pending.push(path);
+                states.push(state);
+            }
+
+            if pending.is_empty() {
+                continue;
+            }
+
+            let evaluations = net.evaluate_batch(&states);
+
+            for ((path, state), (priors, value)) in pending.into_iter().zip(states).zip(evaluations)
+            {
+                path.iter()
+                    .for_each(|n| n.data().stats.revert_virtual_loss());
+
+                let mut leaf = path.last().unwrap().clone();
+                self.expand(&mut leaf, &state, priors.into_iter());
+
+                let simulation = Simulation::Evaluated {
+                    player: state.current_player_turn(),
+                    value,
+                };
+                self.backpropagate(&path, &simulation);
+                done += 1;
+            }
+        }
+
+        self.finish_search(root)
+    }
+
+    /// Information Set MCTS, for games of imperfect information:
+    /// like `search`, but each iteration plays in a fresh `GameState::determinize`
+    /// of `info_state`, descending only through children whose actions are
+    /// legal in that determinization, and adding a child for the first legal
+    /// action the node lacks. Children are shared by action, so their statistics
+    /// accumulate across every determinization in which they are legal.
+    /// Children are selected by UCB1 with the configured exploration constant,
+    /// in place of any other selection policy.
+    /// Panics if `info_state` has no legal actions.
+    pub fn search_ismcts(&mut self, info_state: G::GameState, iterations: usize) -> G::GameAction {
+        let root = self.take_root();
+
+        for done in 1..=iterations {
+            self.iterate_ismcts(&root, &info_state);
+
+            let decided = self.is_decided(&root, done, iterations);
+            self.report_progress(&root, done, decided || done == iterations);
+            if decided {
+                break;
+            }
+        }
+
+        self.finish_search(root)
+    }
+
+    /// Like `search`, but keeps iterating until `budget` has elapsed
+    /// rather than for a fixed count.
+    /// At least one iteration always runs, and the iteration in flight
+    /// when the deadline passes is completed before returning.
+    pub fn search_for(&mut self, root_state: G::GameState, budget: Duration) -> G::GameAction {
+        let deadline = Instant::now() + budget;
+        let root = self.take_root();
+        let mut done = 0;
+
+        loop {
+            self.iterate(&root, &root_state);
+            done += 1;
+            self.report_progress(&root, done, false);
+
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        self.finish_search(root)
+    }
+
+    /// Root parallelization: runs `threads` independent searches of `iterations_per_thread`
+    /// each on their own trees, with their own seeds, then returns the action
+    /// with the most visits summed across all of their roots.
+    /// This searcher's settings are shared by every worker, but its own tree is left untouched.
+    /// Panics if `root_state` has no legal actions.
+    pub fn search_parallel(
+        &self,
+        root_state: G::GameState,
+        iterations_per_thread: usize,
+        threads: usize,
+    ) -> G::GameAction
+    where
+        G::GameState: Send,
+        G::GameAction: Send,
+    {
+        let merged = self.parallel_root_visits(root_state, iterations_per_thread, threads);
+
+        first_max_by(merged, |&(_, visits)| visits)
+            .map(|(action, _)| action)
+            .expect("Cannot search a state with no legal actions.")
+    }
+
+    /// The visits of each root action, summed across the roots of `search_parallel`'s workers.
+    fn parallel_root_visits(
+        &self,
+        root_state: G::GameState,
+        iterations_per_thread: usize,
+        threads: usize,
+    ) -> Vec<(G::GameAction, u32)>
+    where
+        G::GameState: Send,
+        G::GameAction: Send,
+    {
+        let config = self.config();
+        let seeds = self.worker_seeds(threads);
+
+        let per_thread: Vec<Vec<(G::GameAction, u32)>> = thread::scope(|scope| {
+            let handles: Vec<_> = seeds
+                .into_iter()
+                .map(|seed| {
+                    let config = &config;
+                    let state = root_state.clone();
+                    scope.spawn(move || {
+                        let mut mcts = config.build(seed);
+                        mcts.search(state, iterations_per_thread);
+                        mcts.root_visits()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("A search worker panicked."))
+                .collect()
+        });
+
+        let mut merged: Vec<(G::GameAction, u32)> = Vec::new();
+        for (action, visits) in per_thread.into_iter().flatten() {
+            match merged.iter_mut().find(|(a, _)| *a == action) {
+                Some((_, total)) => *total += visits,
+                None => merged.push((action, visits)),
+            }
+        }
+
+        merged
+    }
+
+    fn root_visits(&self) -> Vec<(G::GameAction, u32)> {
+        self.root_statistics()
+            .into_iter()
+            .map(|stat| (stat.action, stat.visits))
+            .collect()
+    }
+
+    /// Searches `state` like `search`, but rather than choosing a move,
+    /// returns an evaluation of every root action, most-visited first.
+    /// Empty if `state` has no legal actions.
+    pub fn analyze(&mut self, state: G::GameState, iterations: usize) -> Vec<MoveAnalysis<G>> {
+        let root = self.take_root();
+
+        for _ in 0..iterations {
+            self.iterate(&root, &state);
+        }
+
+        self.root = Some(root);
+        self.reuse_root = false;
+
+        let mut analysis: Vec<MoveAnalysis<G>> = self
+            .root_statistics()
+            .into_iter()
+            .map(|stat| MoveAnalysis {
+                action: stat.action,
+                visits: stat.visits,
+                win_rate: stat.win_rate,
+                mean_value: stat.mean_reward,
+            })
+            .collect();
+        analysis.sort_by_key(|m| Reverse(m.visits));
+
+        analysis
+    }
+
+    /// Plays a whole game from `start`, searching `iterations_per_move` for every move
+    /// on both sides and playing the most-visited action, reusing the tree between moves.
+    /// Returns one sample per move, in play order, each with the game's final outcome.
+    pub fn self_play(
+        &mut self,
+        start: G::GameState,
+        iterations_per_move: usize,
+    ) -> Vec<TrainingSample<G>> {
+        let mut state = start;
+        let mut moves = Vec::new();
+
+        let outcome = loop {
+            if let Some(outcome) = state.outcome() {
+                break outcome;
+            }
+
+            let action = self.search(state.clone(), iterations_per_move);
+            let stats = self.root_statistics();
+            let total: u32 = stats.iter().map(|stat| stat.visits).sum();
+            let policy = stats
+                .into_iter()
+                .map(|stat| (stat.action, f64::from(stat.visits) / f64::from(total)))
+                .collect();
+
+            let next = state.next(action);
+            moves.push((state, policy, action));
+            self.advance_root(action);
+            state = next;
+        };
+
+        moves
+            .into_iter()
+            .map(|(state, policy, action)| TrainingSample {
+                state,
+                policy,
+                action,
+                outcome,
+            })
+            .collect()
+    }
+
+    /// Makes the root's child reached by `action` the new root,
+    /// discarding the rest of the tree, so that the next search
+    /// continues from the statistics already gathered for that child.
+    /// If that child was never expanded, the next search starts fresh.
+    pub fn advance_root(&mut self, action: G::GameAction) {
+        let child = self.root.as_ref().and_then(|root| {
+            root.children()
+                .iter()
+                .find(|c| c.data().action() == Some(action))
+                .cloned()
+        });
+
+        self.root = child.map(|c| c.detach());
+        self.reuse_root = true;
+    }
+
+    /// The root to search from: the one kept by `advance_root`, if any,
+    /// otherwise a fresh one.
+    fn take_root(&mut self) -> Node<NodeData<G>> {
+        let kept = if self.reuse_root {
+            self.root.take()
+        } else {
+            None
+        };
+
+        // Statistics from an abandoned tree would otherwise leak into the fresh one.
+        if kept.is_none() {
+            if let Some(table) = self.transpositions.as_ref() {
+                table.clear();
+            }
+        }
+
+        let root = kept.unwrap_or_else(|| Node::new(NodeData::root()));
+        self.node_count.set(root.count_subtree_nodes());
+
+        root
+    }
+
+    /// Whether early stopping is due after `done` of `iterations`
+    /// and the remaining iterations can no longer overtake the most-visited root child.
+    fn is_decided(&self, root: &Node<NodeData<G>>, done: usize, iterations: usize) -> bool {
+        match self.early_stop {
+            Some(every) if done.is_multiple_of(every) => {}
+            _ => return false,
+        }
+
+        let mut visits: Vec<u32> = root.children().iter().map(|c| c.data().visits()).collect();
+        visits.sort_unstable_by(|a, b| b.cmp(a));
+
+        match visits.as_slice() {
+            [] => false,
+            [best] => *best as usize > iterations - done,
+            [best, second, ..] => (best - second) as usize > iterations - done,
+        }
+    }
+
+    /// Passes a snapshot of the search to the progress callback, if there is one,
+    /// when `done` is a multiple of its interval or `last` is set.
+    fn report_progress(&mut self, root: &Node<NodeData<G>>, done: usize, last: bool) {
+        let (every, callback) = match self.progress.as_mut() {
+            Some(progress) => progress,
+            None => return,
+        };
+
+        if !done.is_multiple_of(*every) && !last {
+            return;
+        }
+
+        let best = first_max_by(root.children().iter().cloned(), |c| c.data().visits());
+        callback(SearchProgress {
+            iterations_done: done,
+            best_action: best.as_ref().and_then(|c| c.data().action()),
+            best_win_rate: best.map_or(0.0, |c| c.data().win_rate()),
+            nodes_in_tree: root.count_subtree_nodes(),
+        });
+    }
+
+    fn finish_search(&mut self, root: Node<NodeData<G>>) -> G::GameAction {
+        self.root = Some(root);
+        self.reuse_root = false;
+
+        self.best_action(FinalMoveSelection::MaxVisits)
+            .expect("Cannot search a state with no legal actions.")
+    }
+
+    /// The tree built by the most recent search, if any.
+    pub fn root(&self) -> Option<&Node<NodeData<G>>> {
+        self.root.as_ref()
+    }
+
+    /// Samples a root action with probability proportional to
+    /// `visits ^ (1 / temperature)`, as used to generate varied self-play games.
+    /// As the temperature grows, this approaches a uniform choice among root children;
+    /// as it shrinks, it approaches always taking the most-visited child,
+    /// which is exactly what a temperature of 0 does.
+    /// Panics if there has been no search.
+    pub fn sample_action(&mut self, temperature: f64) -> G::GameAction {
+        if temperature <= 0.0 {
+            return self
+                .best_action(FinalMoveSelection::MaxVisits)
+                .expect("Cannot sample an action before searching.");
+        }
+
+        let root = self
+            .root
+            .as_ref()
+            .expect("Cannot sample an action before searching.");
+        let children = root.children();
+
+        // Scaling by the largest count first keeps the powers from overflowing.
+        let max_visits = children
+            .iter()
+            .map(|c| c.data().visits())
+            .max()
+            .unwrap_or(0);
+        let weights: Vec<f64> = children
+            .iter()
+            .map(|c| match max_visits {
+                0 => 1.0,
+                max => (f64::from(c.data().visits()) / f64::from(max)).powf(1.0 / temperature),
+            })
+            .collect();
+
+        let index = WeightedIndex::new(&weights)
+            .expect("Cannot sample an action from a root with no children.");
+
+        children[self.rng.sample(index)].data().action().unwrap()
+    }
+
+    /// Mixes Dirichlet noise into the priors of the root's children,
+    /// as in AlphaZero self-play: `P' = (1 - epsilon) * P + epsilon * eta`,
+    /// where `eta` is drawn from `Dir(alpha)`.
+    /// Call this after the root has been expanded, but before it is searched further;
+    /// it does nothing if there is no root or the root has no children.
+    pub fn add_root_dirichlet_noise(&mut self, alpha: f64, epsilon: f64) {
+        let root = match self.root.as_ref() {
+            Some(root) => root,
+            None => return,
+        };
+        let children = root.children();
+
+        // A Dirichlet sample is a vector of independent Gamma(alpha, 1) samples, normalized.
+        let gamma = Gamma::new(alpha, 1.0).expect("Dirichlet alpha must be positive.");
+        let rng = &mut self.rng;
+        let samples: Vec<f64> = children.iter().map(|_| rng.sample(gamma)).collect();
+        let total: f64 = samples.iter().sum();
+
+        for (child, sample) in children.iter().zip(samples) {
+            let eta = if total > 0.0 {
+                sample / total
+            } else {
+                1.0 / children.len() as f64
+            };
+
+            let prior = &child.data().prior;
+            prior.set((1.0 - epsilon) * prior.get() + epsilon * eta);
+        }
+    }
+
+    /// Statistics for each of the root's children from the most recent search,
+    /// in the same order as the root's children.
+    /// Empty if there has been no search.
+    pub fn root_statistics(&self) -> Vec<ChildStat<G>> {
+        let root = match self.root.as_ref() {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+
+        root.children()
+            .iter()
+            .map(|c| {
+                let data = c.data();
+                ChildStat {
+                    action: data.action().unwrap(),
+                    visits: data.visits(),
+                    mean_reward: data.mean_reward(),
+                    win_rate: data.win_rate(),
+                }
+            })
+            .collect()
+    }
+
+    /// The line of play the search expects: the actions along the path
+    /// from the root that always steps into the most-visited child,
+    /// stopping at a terminal or unexpanded node, or at children never visited.
+    /// Empty if there has been no search.
+    pub fn principal_variation(&self) -> Vec<G::GameAction> {
+        let mut line = Vec::new();
+        let mut node = match self.root.as_ref() {
+            Some(root) => root.clone(),
+            None => return line,
+        };
+
+        loop {
+            let next = first_max_by(node.children().iter(), |c| c.data().visits())
+                .filter(|c| c.data().visits() > 0)
+                .cloned();
+
+            match next {
+                Some(child) => {
+                    line.push(child.data().action().unwrap());
+                    node = child;
+                }
+                None => return line,
+            }
+        }
+    }
+
+    /// The root action chosen by `strategy` from the most recent search,
+    /// or `None` if there has been no search.
+    pub fn best_action(&self, strategy: FinalMoveSelection) -> Option<G::GameAction> {
+        let root = self.root.as_ref()?;
+        let children = root.children();
+
+        let best = match strategy {
+            FinalMoveSelection::MaxVisits => first_max_by(children.iter(), |c| c.data().visits()),
+            FinalMoveSelection::MaxValue => {
+                first_max_by(children.iter(), |c| c.data().mean_reward())
+            }
+            FinalMoveSelection::Robust => first_max_by(children.iter(), |c| {
+                (c.data().visits(), c.data().mean_reward())
+            }),
+        };
+
+        best.and_then(|c| c.data().action())
+    }
+
+    /// Selection: descends from the root through expanded nodes,
+    /// returning the path taken and the state at its end.
+    fn select_leaf(
+        &mut self,
+        root: &Node<NodeData<G>>,
+        root_state: &G::GameState,
+    ) -> (Vec<Node<NodeData<G>>>, G::GameState) {
+        let mut state = root_state.clone();
+        let mut node = root.clone();
+        let mut path = vec![node.clone()];
+        let root_player = root_state.current_player_turn();
+
+        while let Some(child) = self.next_child(&node, &state, root_player) {
+            if let Some(widened) = self.widen(&mut node, &state) {
+                state.make_next(widened.data().action().unwrap());
+                path.push(widened);
+                break;
+            }
+
+            state.make_next(child.data().action().unwrap());
+            node = child;
+            path.push(node.clone());
+        }
+
+        (path, state)
+    }
+
+    /// Gives `node`, reached in `state`, its next untried child if
+    /// progressive widening now allows it one more, returning the new child.
+    fn widen(
+        &self,
+        node: &mut Node<NodeData<G>>,
+        state: &G::GameState,
+    ) -> Option<Node<NodeData<G>>> {
+        let widening = self.widening?;
+        if node.child_count() >= widening.max_children(node.data().visits())
+            || node.data().untried.borrow().is_empty()
+            || !self.make_room(node, 1)
+        {
+            return None;
+        }
+
+        let (action, prior) = node.data().untried.borrow_mut().pop()?;
+        self.add_children(node, Some(self.child_data(state, action, prior)));
+
+        node.nth_child(node.child_count() - 1)
+    }
+
+    fn iterate(&mut self, root: &Node<NodeData<G>>, root_state: &G::GameState) {
+        #[cfg(feature = "trace")]
+        let _span = tracing::trace_span!("iteration").entered();
+
+        let (mut path, mut state) = self.select_leaf(root, root_state);
+        let mut node = path.last().unwrap().clone();
+
+        // Expansion: a leaf is only expanded once it has been simulated from,
+        // except for the root, which must be expanded to have anything to search.
+        // Rollouts then start from the first new child, while an evaluator
+        // scores the expanded leaf itself, so its children wait for selection.
+        // A policy-value net expands every leaf it scores, giving it priors.
+        let should_expand = node.data().visits() > 0 || node.is_root();
+        let mut net_value = None;
+        if let (Some(net), false) = (self.net.as_ref(), state.is_terminal()) {
+            let (priors, value) = net.evaluate(&state);
+            self.expand(&mut node, &state, priors.into_iter());
+            net_value = Some(value);
+        } else if should_expand && !state.is_terminal() {
+            state.legal_actions_into(&mut self.actions);
+            self.expand(&mut node, &state, self.actions.iter().map(|&a| (a, 0.0)));
+
+            let first = match node.data().kind() {
+                NodeKind::Decision => node.nth_child(0),
+                NodeKind::Chance => self.sample_chance(&node),
+            };
+            if let Some(child) = first.filter(|_| self.evaluator.is_none()) {
+                state.make_next(child.data().action().unwrap());
+                path.push(child);
+            }
+        }
+
+        // Simulation: play out to the end of the game, or evaluate the leaf.
+        let leaf_outcome = state.outcome();
+        let mut played = path_actions(&path);
+        let leaf_value = net_value.or_else(|| match (&self.evaluator, &leaf_outcome) {
+            (Some(evaluator), None) => Some(evaluator.evaluate(&state)),
+            _ => None,
+        });
+        let simulation = match (leaf_outcome, leaf_value) {
+            (Some(outcome), _) => Simulation::Outcome(outcome),
+            (None, Some(value)) => Simulation::Evaluated {
+                player: state.current_player_turn(),
+                value,
+            },
+            (None, None) if self.opponent.is_some() => {
+                let opponent = Arc::clone(self.opponent.as_ref().unwrap());
+                let policy = Arc::clone(&self.rollout);
+                let root_player = root_state.current_player_turn();
+                let outcome = rollout::play_out::<G>(
+                    &state,
+                    &mut self.rng,
+                    &mut played,
+                    |s, actions, rng| {
+                        if s.current_player_turn() == root_player {
+                            policy.choose_action(s, actions, rng)
+                        } else {
+                            Some(opponent.pick_action(s, actions))
+                        }
+                    },
+                    |s, depth| policy.cutoff(s, depth),
+                );
+                trace_event!(winner = ?outcome.winner(), "rollout");
+
+                Simulation::Outcome(outcome)
+            }
+            (None, None) => {
+                let outcome = if self.rave_bias.is_some() {
+                    self.rollout
+                        .rollout_recording(&state, &mut self.rng, &mut played)
+                } else {
+                    self.rollout.rollout(&state, &mut self.rng)
+                };
+                trace_event!(winner = ?outcome.winner(), "rollout");
+
+                Simulation::Outcome(outcome)
+            }
+        };
+
+        let root_player = root_state.current_player_turn();
+        if let Some(leaf_outcome) = leaf_outcome {
+            self.prove(&path, &leaf_outcome, root_player);
+        }
+
+        self.backpropagate_played(&path, &played, &simulation);
+    }
+
+    /// Backpropagation: credits each node on `path` with every player's reward,
+    /// discounted by its distance above the leaf,
+    /// along with the AMAF statistics when RAVE is enabled.
+    fn backpropagate(&self, path: &[Node<NodeData<G>>], simulation: &Simulation<G::GameOutcome>) {
+        self.backpropagate_played(path, &path_actions(path), simulation);
+    }
+
+    /// Like `backpropagate`, but with `played` also holding the rollout's actions.
+    fn backpropagate_played(
+        &self,
+        path: &[Node<NodeData<G>>],
+        played: &[(PlayerId, G::GameAction)],
+        simulation: &Simulation<G::GameOutcome>,
+    ) {
+        let players = path
+            .iter()
+            .filter_map(|n| n.data().player())
+            .map(|p| p.0 + 1)
+            .fold(self.player_count, usize::max);
+        let rewards: Vec<f64> = (0..players)
+            .map(|p| self.simulated_reward(simulation, PlayerId(p)))
+            .collect();
+
+        for (distance, node) in path.iter().rev().enumerate() {
+            node.data()
+                .record_rewards(&rewards, self.discount.powi(distance as i32));
+        }
+
+        trace_event!(path_length = path.len(), "backpropagated");
+
+        if self.rave_bias.is_some() {
+            self.update_amaf(path, played, simulation);
+        }
+    }
+
+    fn iterate_ismcts(&mut self, root: &Node<NodeData<G>>, info_state: &G::GameState) {
+        #[cfg(feature = "trace")]
+        let _span = tracing::trace_span!("iteration").entered();
+
+        let mut state = info_state.determinize(&mut self.rng);
+        let mut node = root.clone();
+        let mut path = vec![node.clone()];
+
+        while !state.is_terminal() {
+            state.legal_actions_into(&mut self.actions);
+
+            let untried = self
+                .actions
+                .iter()
+                .copied()
+                .find(|&a| node.children().iter().all(|c| c.data().action() != Some(a)));
+            if let Some(action) = untried {
+                if self.add_children(&mut node, Some(self.child_data(&state, action, 0.0))) {
+                    state.make_next(action);
+                    path.push(node.nth_child(node.child_count() - 1).unwrap());
+                }
+                break;
+            }
+
+            let parent_visits = node.data().visits();
+            let legal_children = node
+                .children()
+                .iter()
+                .filter(|c| self.actions.contains(&c.data().action().unwrap()))
+                .cloned()
+                .collect::<Vec<_>>();
+            let child = first_max_by(legal_children, |c| {
+                let data = c.data();
+                ucb1(
+                    data.reward_sum(),
+                    data.visits(),
+                    parent_visits,
+                    self.exploration,
+                )
+            })
+            .expect("A state without an outcome must have legal actions.");
+
+            state.make_next(child.data().action().unwrap());
+            node = child;
+            path.push(node.clone());
+        }
+
+        let outcome = self.rollout.rollout(&state, &mut self.rng);
+        self.backpropagate(&path, &Simulation::Outcome(outcome));
+    }
+
+    /// Adds a child to `node`, reached in `state`, for each action and its prior,
+    /// sharing statistics through the transposition table if there is one.
+    fn expand(
+        &self,
+        node: &mut Node<NodeData<G>>,
+        state: &G::GameState,
+        priors: impl Iterator<Item = (G::GameAction, f64)>,
+    ) {
+        if let Some(events) = state.chance_outcomes() {
+            let events = events
+                .into_iter()
+                .map(|(a, p)| self.child_data(state, a, p));
+            if self.add_children(node, events) {
+                node.data().kind.set(NodeKind::Chance);
+            }
+            return;
+        }
+
+        if self.widening.is_none() {
+            self.add_children(
+                node,
+                priors.map(|(a, prior)| self.child_data(state, a, prior)),
+            );
+            return;
+        }
+
+        let mut untried: Vec<_> = priors.collect();
+        untried.sort_by(|a, b| b.1.total_cmp(&a.1));
+        untried.reverse();
+
+        if let Some((action, prior)) = untried.pop() {
+            if !self.add_children(node, Some(self.child_data(state, action, prior))) {
+                untried.push((action, prior));
+            }
+        }
+        *node.data().untried.borrow_mut() = untried;
+    }
+
+    /// Adds `children` to `node`, if `make_room` can fit them all,
+    /// returning whether they were added.
+    fn add_children(
+        &self,
+        node: &mut Node<NodeData<G>>,
+        children: impl IntoIterator<Item = NodeData<G>>,
+    ) -> bool {
+        let children: Vec<_> = children.into_iter().collect();
+        if !self.make_room(node, children.len()) {
+            return false;
+        }
+
+        self.node_count.set(self.node_count.get() + children.len());
+        trace_event!(children = children.len(), "expanded");
+        node.add_all_children_from(&mut self.pool.borrow_mut(), children);
+
+        true
+    }
+
+    /// Returns whether `needed` more nodes fit under `max_nodes`, pruning the tree
+    /// `node` belongs to down to 90% of `max_nodes` first if they do not.
+    /// Each pass takes the children from the least-visited nodes whose children are all leaves,
+    /// sparing `node`'s ancestors.
+    fn make_room(&self, node: &Node<NodeData<G>>, needed: usize) -> bool {
+        let max_nodes = match self.max_nodes {
+            Some(max_nodes) => max_nodes,
+            None => return true,
+        };
+        if self.node_count.get() + needed <= max_nodes {
+            return true;
+        }
+
+        let low_water = max_nodes - max_nodes / 10;
+        let path = node.path_to_root();
+        let root = path.last().unwrap();
+        // Node data lives as long as its node, so its address identifies the node.
+        let spared: HashSet<*const NodeData<G>> =
+            path.iter().map(|p| p.data() as *const _).collect();
+
+        while self.node_count.get() + needed > low_water {
+            let mut prunable: Vec<_> = root
+                .iter_preorder()
+                .filter(|n| {
+                    !n.is_leaf()
+                        && n.children().iter().all(|c| c.is_leaf())
+                        && !spared.contains(&(n.data() as *const _))
+                })
+                .collect();
+            if prunable.is_empty() {
+                return self.node_count.get() + needed <= max_nodes;
+            }
+
+            prunable.sort_by_key(|n| n.data().visits());
+            for mut pruned in prunable {
+                if self.node_count.get() + needed <= low_water {
+                    break;
+                }
+
+                let removed = pruned.child_count();
+                pruned.clear_children_into(&mut self.pool.borrow_mut());
+                self.node_count.set(self.node_count.get() - removed);
+            }
+        }
+
+        true
+    }
+
+    /// The data for a child reached by `action` from `state`, sharing statistics
+    /// through the transposition table if there is one.
+    fn child_data(&self, state: &G::GameState, action: G::GameAction, prior: f64) -> NodeData<G> {
+        let player = Some(state.current_player_turn());
+
+        match self.transpositions.as_ref() {
+            Some(table) => NodeData::with_stats(
+                Some(action),
+                player,
+                prior,
+                table.stats_for(&state.next(action)),
+            ),
+            None => NodeData::with_prior(Some(action), player, prior),
+        }
+    }
+
+    /// The reward of `outcome` to `player`, rescaled into [0, 1] if there are reward bounds.
+    fn outcome_reward(&self, outcome: &G::GameOutcome, player: PlayerId) -> f64 {
+        let reward = self.reward.reward(outcome, player);
+
+        match self.reward_bounds {
+            Some((min, max)) => ((reward - min) / (max - min)).clamp(0.0, 1.0),
+            None => reward,
+        }
+    }
+
+    fn simulated_reward(&self, simulation: &Simulation<G::GameOutcome>, player: PlayerId) -> f64 {
+        match *simulation {
+            Simulation::Outcome(ref outcome) => self.outcome_reward(outcome, player),
+            Simulation::Evaluated {
+                player: to_move,
+                value,
+            } if to_move == player => value,
+            Simulation::Evaluated { value, .. } => 1.0 - value,
+        }
+    }
+
+    /// Credits every child of a node on `path` whose action was played by its player
+    /// at any point after that node, at most once per iteration.
+    /// `played` holds the actions leading down `path` and then those of the rollout,
+    /// so the actions after `path[i]` are `played[i..]`.
+    fn update_amaf(
+        &self,
+        path: &[Node<NodeData<G>>],
+        played: &[(PlayerId, G::GameAction)],
+        simulation: &Simulation<G::GameOutcome>,
+    ) {
+        for (i, node) in path.iter().enumerate() {
+            let later = &played[i..];
+
+            for child in node.children().iter() {
+                let data = child.data();
+                let player = data.player().unwrap();
+                let action = data.action().unwrap();
+
+                if later.iter().any(|&(p, a)| p == player && a == action) {
+                    data.record_amaf(self.simulated_reward(simulation, player));
+                }
+            }
+        }
+    }
+
+    /// MCTS-Solver: marks the terminal leaf at the end of `path` as proven,
+    /// then proves as many of its ancestors as that allows, stopping at
+    /// the first ancestor whose status cannot be settled yet.
+    /// When the player to move differs from the player who moved into a node,
+    /// the game is assumed to be two-player and zero-sum.
+    fn prove(&self, path: &[Node<NodeData<G>>], outcome: &G::GameOutcome, root_player: PlayerId) {
+        let perspective = |node: &Node<NodeData<G>>| node.data().player().unwrap_or(root_player);
+
+        let leaf = path.last().unwrap();
+        let reward = self.outcome_reward(outcome, perspective(leaf));
+        let status = if reward >= 1.0 {
+            ProofStatus::ProvenWin
+        } else if reward <= 0.0 {
+            ProofStatus::ProvenLoss
+        } else {
+            return;
+        };
+        leaf.data().proof.set(status);
+
+        for node in path.iter().rev().skip(1) {
+            // A chance node's value is an expectation, which no single child settles.
+            if node.data().is_proven() || node.data().kind() == NodeKind::Chance {
+                break;
+            }
+
+            let children = node.children();
+            let mover = children[0].data().player().unwrap();
+            if self.opponent.is_some() && mover != root_player {
+                break;
+            }
+
+            // A loss needs every action refuted, including any still untried under widening.
+            let status_for_mover = if children
+                .iter()
+                .any(|c| c.data().proof_status() == ProofStatus::ProvenWin)
+            {
+                ProofStatus::ProvenWin
+            } else if node.data().untried.borrow().is_empty()
+                && children
+                    .iter()
+                    .all(|c| c.data().proof_status() == ProofStatus::ProvenLoss)
+            {
+                ProofStatus::ProvenLoss
+            } else {
+                break;
+            };
+
+            let status = match (perspective(node) == mover, status_for_mover) {
+                (true, status) => status,
+                (false, ProofStatus::ProvenWin) => ProofStatus::ProvenLoss,
+                (false, _) => ProofStatus::ProvenWin,
+            };
+            node.data().proof.set(status);
+        }
+    }
+
+    /// The child to descend into from `node`, reached in `state`: sampled by probability
+    /// at a chance node, the modeled opponent's pick on their plies if it has a child,
+    /// and otherwise chosen by `select_child`.
+    fn next_child(
+        &mut self,
+        node: &Node<NodeData<G>>,
+        state: &G::GameState,
+        root_player: PlayerId,
+    ) -> Option<Node<NodeData<G>>> {
+        match node.data().kind() {
+            NodeKind::Decision => self
+                .modeled_child(node, state, root_player)
+                .or_else(|| self.select_child(node)),
+            NodeKind::Chance => self.sample_chance(node),
+        }
+    }
+
+    /// The child for the modeled opponent's pick in `state`,
+    /// or `None` if no opponent is modeled, the root player is to move,
+    /// or the pick has no child yet.
+    fn modeled_child(
+        &mut self,
+        node: &Node<NodeData<G>>,
+        state: &G::GameState,
+        root_player: PlayerId,
+    ) -> Option<Node<NodeData<G>>> {
+        let opponent = self.opponent.as_ref()?;
+        if state.current_player_turn() == root_player || node.is_leaf() {
+            return None;
+        }
+
+        state.legal_actions_into(&mut self.actions);
+        let pick = opponent.pick_action(state, &self.actions);
+
+        node.children()
+            .iter()
+            .find(|c| c.data().action() == Some(pick))
+            .cloned()
+    }
+
+    /// A child of the chance node `node`, sampled in proportion to its probability,
+    /// or `None` if `node` has no children.
+    fn sample_chance(&mut self, node: &Node<NodeData<G>>) -> Option<Node<NodeData<G>>> {
+        let children = node.children();
+        let weights = WeightedIndex::new(children.iter().map(|c| c.data().prior())).ok()?;
+
+        Some(children[self.rng.sample(weights)].clone())
+    }
+
+    /// The child chosen by the selection policy, or `None` if `node` has no children.
+    /// A proven-winning child is always taken instead.
+    fn select_child(&self, node: &Node<NodeData<G>>) -> Option<Node<NodeData<G>>> {
+        let children = node.children();
+
+        if children.is_empty() {
+            return None;
+        }
+
+        if let Some(winner) = children
+            .iter()
+            .find(|c| c.data().proof_status() == ProofStatus::ProvenWin)
+        {
+            return Some(winner.clone());
+        }
+
+        let index = match self.selection.as_ref() {
+            Some(selection) => selection.select(node),
+            None => Ucb1Policy {
+                exploration: self.exploration,
+                rave_bias: self.rave_bias,
+                fpu: self.fpu,
+            }
+            .select(node),
+        };
+
+        trace_event!(
+            index,
+            visits = children[index].data().visits(),
+            ucb1 = ucb1(
+                children[index].data().reward_sum(),
+                children[index].data().visits(),
+                node.data().visits(),
+                self.exploration,
+            ),
+            "selected child"
+        );
+
+        Some(children[index].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        DrawOutcome, EndlessOutcome, SimpleGame, SimpleGameAction, SimpleGameOutcome,
+        SimpleGameState,
+    };
+    use crate::PlayerColor;
+
+    fn simple_reward(outcome: &SimpleGameOutcome, player: PlayerId) -> f64 {
+        match outcome.winner() {
+            Some(winner) if winner == player => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    #[test]
+    fn search_returns_legal_action() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+        let state = SimpleGameState::new();
+
+        let action = mcts.search(state.clone(), 200);
+
+        let legal: Vec<usize> = state.legal_actions().iter().map(|a| a.bump).collect();
+        assert!(legal.contains(&action.bump));
+    }
+
+    #[test]
+    fn search_visit_counts_sum_to_iterations() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        mcts.search(SimpleGameState::new(), 200);
+
+        let root = mcts.root().unwrap();
+        let child_visits: u32 = root.children().iter().map(|c| c.data().visits()).sum();
+
+        assert_eq!(200, root.data().visits());
+        assert_eq!(200, child_visits);
+        assert_eq!(3, root.child_count());
+    }
+
+    #[test]
+    fn nodes_never_have_more_child_visits_than_own_visits() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        mcts.search(SimpleGameState::new(), 300);
+
+        for node in mcts.root().unwrap().iter_preorder() {
+            let child_visits: u32 = node.children().iter().map(|c| c.data().visits()).sum();
+            let own_visits = node.data().visits();
+
+            assert!(child_visits <= own_visits);
+            assert!(own_visits - child_visits <= 1);
+        }
+    }
+
+    /// A root with one child per bump in `bumps`, none of them visited.
+    fn root_with_children(bumps: &[usize]) -> Node<NodeData<SimpleGame>> {
+        let mut root = Node::new(NodeData::root());
+        root.add_all_children(bumps.iter().map(|&b| {
+            NodeData::with_prior(
+                Some(SimpleGameAction::new(b)),
+                Some(PlayerColor::Black.into()),
+                0.0,
+            )
+        }));
+
+        root
+    }
+
+    #[test]
+    fn ucb1_known_value() {
+        let score = ucb1(3.0, 5, 20, std::f64::consts::SQRT_2);
+
+        let expected = 0.6 + std::f64::consts::SQRT_2 * (20f64.ln() / 5.0).sqrt();
+        assert!((score - expected).abs() < 1e-12);
+        assert!((score - 1.694_666).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ucb1_zero_exploration_is_mean() {
+        assert_eq!(0.25, ucb1(1.0, 4, 100, 0.0));
+    }
+
+    #[test]
+    fn ucb1_unvisited_is_infinite() {
+        assert_eq!(f64::INFINITY, ucb1(0.0, 0, 10, 1.0));
+    }
+
+    #[test]
+    fn select_child_prefers_unvisited() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward);
+        let root = root_with_children(&[2, 3, 4]);
+
+        for _ in 0..10 {
+            root.data().record(1.0);
+            root.children()[0].data().record(1.0);
+        }
+        root.children()[2].data().record(1.0);
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(3, selected.data().action().unwrap().bump);
+    }
+
+    #[test]
+    fn select_child_maximizes_ucb1() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward).with_exploration(0.0);
+        let root = root_with_children(&[2, 3]);
+
+        root.children()[0].data().record(0.0);
+        root.children()[0].data().record(1.0);
+        root.children()[1].data().record(1.0);
+        root.data().record(0.0);
+        root.data().record(0.0);
+        root.data().record(0.0);
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(3, selected.data().action().unwrap().bump);
+    }
+
+    /// Always plays the first legal action.
+    struct FirstActionRollout;
+
+    struct ConstantEvaluator(f64);
+
+    impl StateEvaluator<SimpleGame> for ConstantEvaluator {
+        fn evaluate(&self, _: &SimpleGameState) -> f64 {
+            self.0
+        }
+    }
+
+    struct UnusedRollout;
+
+    impl RolloutPolicy<SimpleGame> for UnusedRollout {
+        fn rollout(&self, _: &SimpleGameState, _: &mut StdRng) -> SimpleGameOutcome {
+            panic!("The evaluator should replace every rollout.");
+        }
+    }
+
+    #[test]
+    fn evaluator_values_are_backpropagated_instead_of_rollouts() {
+        let mut mcts = Mcts::new(simple_reward)
+            .with_rollout_policy(Box::new(UnusedRollout))
+            .with_evaluator(Box::new(ConstantEvaluator(0.7)));
+
+        mcts.search(SimpleGameState::new(), 30);
+
+        let stats = mcts.root_statistics();
+        assert_eq!(29, stats.iter().map(|s| s.visits).sum::<u32>());
+        assert!(stats.iter().all(|s| (s.mean_reward - 0.7).abs() < 1e-12));
+    }
+
+    fn backpropagated_child(discount: f64) -> Node<NodeData<SimpleGame>> {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward).with_discount(discount);
+        let root = root_with_children(&[2]);
+        let child = root.nth_child(0).unwrap();
+        let mut parent = child.clone();
+        parent.add_all_children(vec![NodeData::with_prior(
+            Some(SimpleGameAction::new(2)),
+            Some(PlayerColor::Black.into()),
+            0.0,
+        )]);
+        let leaf = child.nth_child(0).unwrap();
+
+        let simulation = Simulation::Evaluated {
+            player: PlayerColor::Black.into(),
+            value: 1.0,
+        };
+        mcts.backpropagate(&[root, child.clone(), leaf.clone()], &simulation);
+
+        assert_eq!(1.0, leaf.data().mean_reward());
+        child
+    }
+
+    #[test]
+    fn discount_shrinks_rewards_above_the_leaf() {
+        let undiscounted = backpropagated_child(1.0).data().mean_reward();
+        let child = backpropagated_child(0.9);
+        let discounted = child.data().mean_reward();
+
+        assert_eq!(1.0, undiscounted);
+        assert!((discounted - 0.9).abs() < 1e-12);
+        assert!(discounted < undiscounted);
+        // A discounted win still counts as a win.
+        assert_eq!(1, child.data().wins());
+    }
+
+    #[test]
+    fn progress_reports_every_interval_and_after_the_last_iteration() {
+        let snapshots = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&snapshots);
+        let mut mcts = Mcts::new(simple_reward).with_seed(3).with_progress(
+            10,
+            Box::new(move |p: SearchProgress<SimpleGame>| recorded.borrow_mut().push(p)),
+        );
+
+        let best = mcts.search(SimpleGameState::new(), 45);
+
+        let snapshots = snapshots.borrow();
+        let done: Vec<usize> = snapshots.iter().map(|p| p.iterations_done).collect();
+        assert_eq!(vec![10, 20, 30, 40, 45], done);
+
+        let last = snapshots.last().unwrap();
+        assert_eq!(Some(best), last.best_action);
+        assert_eq!(
+            mcts.root().unwrap().count_subtree_nodes(),
+            last.nodes_in_tree
+        );
+        assert!(snapshots
+            .windows(2)
+            .all(|w| w[0].nodes_in_tree <= w[1].nodes_in_tree));
+    }
+
+    #[test]
+    fn early_stop_ends_search_once_the_lead_is_insurmountable() {
+        use crate::tests::{DrawAction, DrawGame, DrawState};
+
+        let mut mcts = Mcts::<DrawGame>::default().with_early_stop(10);
+
+        assert_eq!(DrawAction::Win, mcts.search(DrawState::default(), 1000));
+
+        let visits = mcts.root().unwrap().data().visits();
+        assert!(visits < 1000, "searched all {} iterations", visits);
+        assert_eq!(0, visits % 10);
+    }
+
+    #[test]
+    fn without_early_stop_the_whole_budget_is_searched() {
+        use crate::tests::{DrawGame, DrawState};
+
+        let mut mcts = Mcts::<DrawGame>::default();
+        mcts.search(DrawState::default(), 1000);
+
+        assert_eq!(1000, mcts.root().unwrap().data().visits());
+    }
+
+    #[test]
+    fn max_nodes_caps_the_tree_throughout_the_search() {
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&sizes);
+        let mut mcts = Mcts::new(simple_reward)
+            .with_seed(5)
+            .with_max_nodes(8)
+            .with_progress(
+                1,
+                Box::new(move |p: SearchProgress<SimpleGame>| {
+                    recorded.borrow_mut().push(p.nodes_in_tree)
+                }),
+            );
+
+        // From 38, bumping by 4 lands exactly on 42.
+        let best = mcts.search(state_at(38), 2000);
+
+        let sizes = sizes.borrow();
+        assert_eq!(2000, sizes.len());
+        assert!(sizes.iter().all(|&n| n <= 8));
+        assert_eq!(
+            mcts.root().unwrap().count_subtree_nodes(),
+            mcts.node_count.get()
+        );
+        assert_eq!(SimpleGameAction::new(4), best);
+    }
+
+    #[test]
+    fn max_nodes_reuses_pruned_nodes() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_seed(5)
+            .with_max_nodes(20);
+
+        mcts.search(state_at(30), 2000);
+
+        // Nodes are only allocated while the pool is empty, and the tree holds
+        // at most 19 besides the root, so no more than that are ever allocated.
+        let pool = mcts.pool.borrow();
+        assert!(pool.allocated() <= 19);
+        assert!(pool.reused() > pool.allocated());
+    }
+
+    #[test]
+    fn max_nodes_prunes_in_batches() {
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&sizes);
+        let mut mcts = Mcts::new(simple_reward)
+            .with_seed(5)
+            .with_max_nodes(200)
+            .with_progress(
+                1,
+                Box::new(move |p: SearchProgress<SimpleGame>| {
+                    recorded.borrow_mut().push(p.nodes_in_tree)
+                }),
+            );
+
+        mcts.search(state_at(0), 2000);
+
+        // Each prune frees a tenth of the cap, visible as the tree shrinking
+        // between iterations, and leaves room for several expansions before the next.
+        let sizes = sizes.borrow();
+        let prunes = sizes.windows(2).filter(|w| w[1] < w[0]).count();
+        assert!(sizes.iter().all(|&n| n <= 200));
+        assert!(prunes > 0);
+        assert!(prunes < sizes.len() / 5);
+    }
+
+    #[test]
+    fn max_nodes_prunes_only_once_the_cap_is_reached() {
+        let mut capped = Mcts::<SimpleGame>::new(simple_reward)
+            .with_seed(5)
+            .with_max_nodes(1000);
+        let mut uncapped = Mcts::<SimpleGame>::new(simple_reward).with_seed(5);
+
+        capped.search(state_at(30), 100);
+        uncapped.search(state_at(30), 100);
+
+        assert_eq!(
+            uncapped.root().unwrap().count_subtree_nodes(),
+            capped.root().unwrap().count_subtree_nodes()
+        );
+    }
+
+    #[cfg(feature = "trace")]
+    mod tracing_events {
+        use super::*;
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records the name of each span and the message of each event.
+        #[derive(Default)]
+        struct Capture(Arc<Mutex<Vec<String>>>);
+
+        struct Message<'a>(&'a mut Option<String>);
+
+        impl Visit for Message<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    *self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("span {}", span.metadata().name()));
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut message = None;
+                event.record(&mut Message(&mut message));
+                self.0.lock().unwrap().extend(message);
+            }
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        #[test]
+        fn search_emits_an_event_for_each_phase() {
+            let captured = Arc::new(Mutex::new(Vec::new()));
+            let subscriber = Capture(Arc::clone(&captured));
+
+            tracing::subscriber::with_default(subscriber, || {
+                Mcts::<SimpleGame>::new(simple_reward)
+                    .with_seed(1)
+                    .search(SimpleGameState::new(), 5);
+            });
+
+            let captured = captured.lock().unwrap();
+            let count = |name: &str| captured.iter().filter(|c| *c == name).count();
+            assert_eq!(5, count("span iteration"));
+            assert_eq!(5, count("backpropagated"));
+            assert!(count("expanded") >= 1);
+            assert!(count("selected child") >= 4);
+            assert!(count("rollout") >= 1);
+        }
+    }
+
+    fn wide_reward(outcome: &SimpleGameOutcome, player: PlayerId) -> f64 {
+        match outcome.winner() {
+            Some(winner) if winner == player => 10.0,
+            _ => -10.0,
+        }
+    }
+
+    #[test]
+    fn reward_bounds_normalize_rewards_for_ucb() {
+        let mut mcts = Mcts::<SimpleGame>::new(wide_reward)
+            .with_seed(4)
+            .with_reward_bounds(-10.0, 10.0);
+
+        mcts.search(state_at(30), 300);
+
+        let root = mcts.root().unwrap();
+        let parent_visits = root.data().visits();
+        let bonus_cap = std::f64::consts::SQRT_2 * (f64::from(parent_visits).ln()).sqrt();
+        for child in root.children().iter() {
+            let data = child.data();
+            assert!((0.0..=1.0).contains(&data.mean_reward()));
+
+            let score = ucb1(
+                data.reward_sum(),
+                data.visits(),
+                parent_visits,
+                std::f64::consts::SQRT_2,
+            );
+            assert!(score >= 0.0 && score <= 1.0 + bonus_cap, "score {}", score);
+        }
+    }
+
+    #[test]
+    fn reward_bounds_rescale_to_the_unit_interval() {
+        let mcts = Mcts::<SimpleGame>::new(wide_reward).with_reward_bounds(-10.0, 10.0);
+        let black = PlayerColor::Black.into();
+
+        assert_eq!(
+            1.0,
+            mcts.outcome_reward(&SimpleGameOutcome::BlackWins, black)
+        );
+        assert_eq!(
+            0.0,
+            mcts.outcome_reward(&SimpleGameOutcome::BothLose, black)
+        );
+    }
+
+    struct Margin;
+
+    /// Scores by how far the winner is ahead, as a game with scores might.
+    impl Reward<SimpleGame> for Margin {
+        fn reward(&self, outcome: &SimpleGameOutcome, perspective: PlayerId) -> f64 {
+            match outcome.winner() {
+                Some(winner) if winner == perspective => 3.0,
+                Some(_) => -3.0,
+                None => 0.0,
+            }
+        }
+    }
+
+    #[test]
+    fn custom_reward_types_score_outcomes() {
+        let mcts = Mcts::<SimpleGame>::new(Margin);
+
+        assert_eq!(
+            -3.0,
+            mcts.outcome_reward(&SimpleGameOutcome::BlackWins, PlayerColor::White.into())
+        );
+    }
+
+    #[test]
+    fn rewards_follow_each_nodes_player_along_the_path() {
+        let mcts = Mcts::<SimpleGame>::new(winner_reward::<SimpleGameOutcome>);
+        let root = root_with_children(&[2]);
+        let mut black_move = root.nth_child(0).unwrap();
+        black_move.add_all_children(vec![NodeData::with_prior(
+            Some(SimpleGameAction::new(3)),
+            Some(PlayerColor::White.into()),
+            0.0,
+        )]);
+        let white_move = black_move.nth_child(0).unwrap();
+        let path = [root, black_move.clone(), white_move.clone()];
+
+        mcts.backpropagate(&path, &Simulation::Outcome(SimpleGameOutcome::BlackWins));
+        assert_eq!(1.0, black_move.data().reward_sum());
+        assert_eq!(0.0, white_move.data().reward_sum());
+
+        mcts.backpropagate(&path, &Simulation::Outcome(SimpleGameOutcome::WhiteWins));
+        assert_eq!(1.0, black_move.data().reward_sum());
+        assert_eq!(1.0, white_move.data().reward_sum());
+
+        mcts.backpropagate(&path, &Simulation::Outcome(SimpleGameOutcome::BothLose));
+        assert_eq!(1.5, black_move.data().reward_sum());
+        assert_eq!(1.5, white_move.data().reward_sum());
+    }
+
+    struct UniformNet(f64);
+
+    impl PolicyValueNet<SimpleGame> for UniformNet {
+        fn evaluate(&self, state: &SimpleGameState) -> Evaluation<SimpleGame> {
+            let actions = state.legal_actions();
+            let prior = 1.0 / actions.len() as f64;
+
+            (actions.into_iter().map(|a| (a, prior)).collect(), self.0)
+        }
+    }
+
+    #[test]
+    fn policy_value_net_sets_priors_and_backs_up_values() {
+        let mut mcts = Mcts::new(simple_reward)
+            .with_rollout_policy(Box::new(UnusedRollout))
+            .with_policy_value_net(Box::new(UniformNet(0.6)));
+
+        mcts.search(SimpleGameState::new(), 20);
+
+        let root = mcts.root().unwrap();
+        let children = root.children();
+        assert!(children
+            .iter()
+            .all(|c| (c.data().prior() - 1.0 / 3.0).abs() < 1e-12));
+        assert_eq!(19, children.iter().map(|c| c.data().visits()).sum::<u32>());
+        assert!(children
+            .iter()
+            .all(|c| (c.data().mean_reward() - 0.6).abs() < 1e-12));
+
+        // Every visited leaf was expanded as soon as the net scored it.
+        let visited = children.iter().find(|c| c.data().visits() > 0).unwrap();
+        assert_eq!(3, visited.children().len());
+    }
+
+    /// A `UniformNet` that logs the size of every batch it evaluates.
+    struct CountingNet(Arc<std::sync::Mutex<Vec<usize>>>);
+
+    impl PolicyValueNet<SimpleGame> for CountingNet {
+        fn evaluate(&self, state: &SimpleGameState) -> Evaluation<SimpleGame> {
+            UniformNet(0.5).evaluate(state)
+        }
+
+        fn evaluate_batch(&self, states: &[SimpleGameState]) -> Vec<Evaluation<SimpleGame>> {
+            self.0.lock().unwrap().push(states.len());
+            states.iter().map(|s| self.evaluate(s)).collect()
+        }
+    }
+
+    #[test]
+    fn search_batched_evaluates_leaves_in_batches() {
+        let batches = Arc::default();
+        let mut mcts = Mcts::new(simple_reward)
+            .with_policy_value_net(Box::new(CountingNet(Arc::clone(&batches))))
+            .with_batch_size(3);
+
+        mcts.search_batched(SimpleGameState::new(), 13);
+
+        // The root is evaluated alone, since it is the only leaf at first.
+        assert_eq!(vec![1, 3, 3, 3, 3], *batches.lock().unwrap());
+
+        let root = mcts.root().unwrap();
+        assert_eq!(13, root.data().visits());
+        assert_eq!(
+            12,
+            root.children()
+                .iter()
+                .map(|c| c.data().visits())
+                .sum::<u32>()
+        );
+    }
+
+    /// Fifty actions a move, for four moves.
+    struct WideGame;
+
+    #[derive(Clone)]
+    struct WideState(usize);
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    struct Pick(usize);
+
+    impl crate::GameAction for Pick {}
+
+    impl GameState for WideState {
+        type Action = Pick;
+        type Outcome = EndlessOutcome;
+
+        fn make_next(&mut self, _: Pick) {
+            self.0 += 1;
+        }
+
+        fn legal_actions(&self) -> Vec<Pick> {
+            (0..50).map(Pick).collect()
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<EndlessOutcome> {
+            Some(EndlessOutcome { moves: self.0 }).filter(|_| self.0 >= 4)
+        }
+    }
+
+    impl Game for WideGame {
+        type GameState = WideState;
+        type GameAction = Pick;
+        type GameOutcome = EndlessOutcome;
+    }
+
+    /// Black plays a card, winning if it matches the opponent's hidden card and
+    /// drawing otherwise. The hidden card is 1 or 2, and the cards Black may play
+    /// are 0 and the hidden card, so an undeterminized state allows any card.
+    #[derive(Clone, Default)]
+    struct HiddenCardState {
+        hidden: Option<usize>,
+        played: Option<usize>,
+    }
+
+    struct HiddenCardGame;
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    struct Card(usize);
+
+    impl crate::GameAction for Card {}
+
+    impl GameState for HiddenCardState {
+        type Action = Card;
+        type Outcome = DrawOutcome;
+
+        fn make_next(&mut self, action: Card) {
+            self.played = Some(action.0);
+        }
+
+        fn legal_actions(&self) -> Vec<Card> {
+            match self.hidden {
+                Some(hidden) => vec![Card(0), Card(hidden)],
+                None => (0..4).map(Card).collect(),
+            }
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<DrawOutcome> {
+            self.played.map(|card| match self.hidden {
+                Some(hidden) if hidden == card => DrawOutcome::BlackWins,
+                _ => DrawOutcome::Drawn,
+            })
+        }
+
+        fn determinize(&self, rng: &mut impl Rng) -> Self {
+            Self {
+                hidden: Some(rng.gen_range(1..=2)),
+                played: self.played,
+            }
+        }
+    }
+
+    impl Game for HiddenCardGame {
+        type GameState = HiddenCardState;
+        type GameAction = Card;
+        type GameOutcome = DrawOutcome;
+    }
+
+    #[test]
+    fn ismcts_expands_only_determinized_actions() {
+        let mut mcts = Mcts::<HiddenCardGame>::default().with_seed(5);
+
+        mcts.search_ismcts(HiddenCardState::default(), 200);
+
+        let stats = mcts.root_statistics();
+        let mut cards: Vec<usize> = stats.iter().map(|s| s.action.0).collect();
+        cards.sort_unstable();
+        assert_eq!(vec![0, 1, 2], cards);
+
+        // Statistics gathered under each determinization land on the same children.
+        assert_eq!(200, stats.iter().map(|s| s.visits).sum::<u32>());
+        assert!(stats.iter().all(|s| s.visits > 1));
+    }
+
+    /// Three players each cast one `Vote`, in turn, and the winner is
+    /// the player numbered by the votes' total, modulo three.
+    struct VoteGame;
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    struct Vote(usize);
+
+    impl crate::GameAction for Vote {}
+
+    #[derive(Copy, Clone, Debug)]
+    struct VoteOutcome(PlayerId);
+
+    impl GameOutcome for VoteOutcome {
+        fn winner(&self) -> Option<PlayerId> {
+            Some(self.0)
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct VoteState(Vec<usize>);
+
+    impl GameState for VoteState {
+        type Action = Vote;
+        type Outcome = VoteOutcome;
+
+        fn make_next(&mut self, action: Vote) {
+            self.0.push(action.0);
+        }
+
+        fn legal_actions(&self) -> Vec<Vote> {
+            (0..3).map(Vote).collect()
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerId(self.0.len())
+        }
+
+        fn outcome(&self) -> Option<VoteOutcome> {
+            Some(VoteOutcome(PlayerId(self.0.iter().sum::<usize>() % 3)))
+                .filter(|_| self.0.len() == 3)
+        }
+    }
+
+    impl Game for VoteGame {
+        type GameState = VoteState;
+        type GameAction = Vote;
+        type GameOutcome = VoteOutcome;
+    }
+
+    #[test]
+    fn each_players_reward_accumulates_independently() {
+        let mcts = Mcts::<VoteGame>::new(winner_reward::<VoteOutcome>).with_player_count(3);
+        let mut path = vec![Node::new(NodeData::root())];
+        for player in 0..3 {
+            let mut last = path[player].clone();
+            last.add_all_children(vec![NodeData::with_prior(
+                Some(Vote(0)),
+                Some(PlayerId(player)),
+                0.0,
+            )]);
+            path.push(last.nth_child(0).unwrap());
+        }
+
+        mcts.backpropagate(&path, &Simulation::Outcome(VoteOutcome(PlayerId(2))));
+        mcts.backpropagate(&path, &Simulation::Outcome(VoteOutcome(PlayerId(0))));
+        mcts.backpropagate(&path, &Simulation::Outcome(VoteOutcome(PlayerId(2))));
+
+        for node in &path {
+            let data = node.data();
+            assert_eq!(3, data.visits());
+            assert_eq!(1.0, data.reward_sum_for(PlayerId(0)));
+            assert_eq!(0.0, data.reward_sum_for(PlayerId(1)));
+            assert_eq!(2.0, data.reward_sum_for(PlayerId(2)));
+        }
+
+        // Each node's own reward is its mover's entry.
+        assert_eq!(0.0, path[2].data().reward_sum());
+        assert_eq!(2.0, path[3].data().reward_sum());
+    }
+
+    #[test]
+    fn search_credits_exactly_one_winner_per_visit() {
+        let mut mcts = Mcts::<VoteGame>::default()
+            .with_player_count(3)
+            .with_seed(3);
+
+        mcts.search(VoteState::default(), 200);
+
+        for node in mcts.root().unwrap().iter_preorder() {
+            let data = node.data();
+            let total: f64 = (0..3).map(|p| data.reward_sum_for(PlayerId(p))).sum();
+            assert_eq!(f64::from(data.visits()), total);
+        }
+    }
+
+    /// Black makes a single `Choice`, and only the last one wins.
+    struct LastChanceGame;
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    struct Choice(usize);
+
+    impl crate::GameAction for Choice {}
+
+    #[derive(Clone, Default)]
+    struct LastChanceState(Option<usize>);
+
+    impl GameState for LastChanceState {
+        type Action = Choice;
+        type Outcome = SimpleGameOutcome;
+
+        fn make_next(&mut self, action: Choice) {
+            self.0 = Some(action.0);
+        }
+
+        fn legal_actions(&self) -> Vec<Choice> {
+            (0..4).map(Choice).collect()
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<SimpleGameOutcome> {
+            self.0.map(|choice| match choice {
+                3 => SimpleGameOutcome::BlackWins,
+                _ => SimpleGameOutcome::WhiteWins,
+            })
+        }
+    }
+
+    impl Game for LastChanceGame {
+        type GameState = LastChanceState;
+        type GameAction = Choice;
+        type GameOutcome = SimpleGameOutcome;
+    }
+
+    #[test]
+    fn incremental_expansion_does_not_prove_a_loss_with_actions_untried() {
+        let mut mcts = Mcts::<LastChanceGame>::default()
+            .with_seed(1)
+            .with_incremental_expansion();
+
+        mcts.search(LastChanceState::default(), 1);
+        let root = mcts.root().unwrap();
+        assert_eq!(
+            ProofStatus::ProvenLoss,
+            root.children()[0].data().proof_status()
+        );
+        assert_eq!(
+            Some(Choice(3)),
+            root.data().untried.borrow().first().map(|u| u.0)
+        );
+        assert_eq!(ProofStatus::Unknown, root.data().proof_status());
+
+        let action = mcts.search(LastChanceState::default(), 20);
+
+        assert_eq!(Choice(3), action);
+        assert_eq!(
+            ProofStatus::ProvenWin,
+            mcts.root().unwrap().data().proof_status()
+        );
+    }
+
+    #[test]
+    fn solver_waits_for_widening_to_try_every_action() {
+        let mut mcts = Mcts::<LastChanceGame>::default()
+            .with_seed(1)
+            .with_progressive_widening(1.0, 0.5);
+
+        // The root is only widened to its last, winning action after its 9th visit.
+        mcts.search(LastChanceState::default(), 9);
+        let root = mcts.root().unwrap();
+        assert_eq!(3, root.child_count());
+        assert!(root
+            .children()
+            .iter()
+            .all(|c| c.data().proof_status() == ProofStatus::ProvenLoss));
+        assert_eq!(ProofStatus::Unknown, root.data().proof_status());
+
+        let action = mcts.search(LastChanceState::default(), 20);
+
+        assert_eq!(Choice(3), action);
+        assert_eq!(
+            ProofStatus::ProvenWin,
+            mcts.root().unwrap().data().proof_status()
+        );
+    }
+
+    /// Black either plays `Safe`, drawing at once, or `Gamble`s, after which
+    /// White wins with `Counter` but loses by taking the `Bait`.
+    struct TrapGame;
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    enum Trap {
+        Safe,
+        Gamble,
+        Bait,
+        Counter,
+    }
+
+    impl crate::GameAction for Trap {}
+
+    #[derive(Clone, Default)]
+    struct TrapState {
+        black: Option<Trap>,
+        white: Option<Trap>,
+    }
+
+    impl GameState for TrapState {
+        type Action = Trap;
+        type Outcome = SimpleGameOutcome;
+
+        fn make_next(&mut self, action: Trap) {
+            match self.black {
+                None => self.black = Some(action),
+                Some(_) => self.white = Some(action),
+            }
+        }
+
+        fn legal_actions(&self) -> Vec<Trap> {
+            match self.black {
+                None => vec![Trap::Safe, Trap::Gamble],
+                Some(_) => vec![Trap::Bait, Trap::Counter],
+            }
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            match self.black {
+                None => PlayerColor::Black.into(),
+                Some(_) => PlayerColor::White.into(),
+            }
+        }
+
+        fn outcome(&self) -> Option<SimpleGameOutcome> {
+            match (self.black?, self.white) {
+                (Trap::Safe, _) => Some(SimpleGameOutcome::BothLose),
+                (_, Some(Trap::Bait)) => Some(SimpleGameOutcome::BlackWins),
+                (_, Some(_)) => Some(SimpleGameOutcome::WhiteWins),
+                (_, None) => None,
+            }
+        }
+    }
+
+    impl Game for TrapGame {
+        type GameState = TrapState;
+        type GameAction = Trap;
+        type GameOutcome = SimpleGameOutcome;
+    }
+
+    /// A greedy opponent who always takes the bait.
+    struct TakesBait;
+
+    impl GameAgent<TrapGame> for TakesBait {
+        fn pick_action(&self, _: &TrapState, actions: &[Trap]) -> Trap {
+            *actions
+                .iter()
+                .find(|&&a| a == Trap::Bait)
+                .unwrap_or(&actions[0])
+        }
+    }
+
+    fn trap_reward(outcome: &SimpleGameOutcome, player: PlayerId) -> f64 {
+        match outcome.winner() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+
+    #[test]
+    fn self_play_assumption_avoids_the_gamble() {
+        let mut mcts = Mcts::<TrapGame>::new(trap_reward).with_seed(1);
+
+        assert_eq!(Trap::Safe, mcts.search(TrapState::default(), 500));
+    }
+
+    #[test]
+    fn opponent_model_exploits_a_greedy_opponent() {
+        let mut mcts = Mcts::<TrapGame>::new(trap_reward)
+            .with_seed(1)
+            .with_opponent_model(Box::new(TakesBait));
+
+        assert_eq!(Trap::Gamble, mcts.search(TrapState::default(), 500));
+
+        let gamble = mcts.root().unwrap().nth_child(1).unwrap();
+        assert_eq!(1.0, gamble.data().mean_reward());
+    }
+
+    /// Plays each of its plies through `choose_action`, counting them.
+    struct CountingRollout(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl RolloutPolicy<SimpleGame> for CountingRollout {
+        fn rollout(&self, _: &SimpleGameState, _: &mut StdRng) -> SimpleGameOutcome {
+            panic!("The opponent model should play rollouts ply by ply.");
+        }
+
+        fn choose_action(
+            &self,
+            _: &SimpleGameState,
+            actions: &[SimpleGameAction],
+            _: &mut StdRng,
+        ) -> Option<SimpleGameAction> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            actions.first().copied()
+        }
+    }
+
+    /// Always plays the first legal action.
+    struct FirstActionAgent;
+
+    impl<G: Game> GameAgent<G> for FirstActionAgent {
+        fn pick_action(&self, _: &G::GameState, actions: &[G::GameAction]) -> G::GameAction {
+            actions[0]
+        }
+    }
+
+    #[test]
+    fn opponent_model_plays_the_searchers_plies_by_the_rollout_policy() {
+        let plies = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut mcts = Mcts::new(simple_reward)
+            .with_seed(1)
+            .with_rollout_policy(Box::new(CountingRollout(Arc::clone(&plies))))
+            .with_opponent_model(Box::new(FirstActionAgent));
+
+        mcts.search(SimpleGameState::new(), 50);
+
+        assert!(plies.load(std::sync::atomic::Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn opponent_model_keeps_the_max_rollout_depth() {
+        use crate::tests::{count_moves, EndlessGame, EndlessState};
+
+        let mut mcts = Mcts::<EndlessGame>::new(|outcome: &EndlessOutcome, _| {
+            1.0 / (1.0 + outcome.moves as f64)
+        })
+        .with_max_rollout_depth(10, count_moves)
+        .with_opponent_model(Box::new(FirstActionAgent));
+
+        mcts.search(EndlessState::default(), 100);
+
+        assert_eq!(100, mcts.root().unwrap().data().visits());
+    }
+
+    /// A coin landing heads three times in four is tossed, then the game ends.
+    struct CoinGame;
+
+    #[derive(Clone, Default)]
+    struct CoinState {
+        heads: Option<bool>,
+    }
+
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    struct Toss(bool);
+
+    impl crate::GameAction for Toss {}
+
+    impl GameState for CoinState {
+        type Action = Toss;
+        type Outcome = EndlessOutcome;
+
+        fn make_next(&mut self, action: Toss) {
+            self.heads = Some(action.0);
+        }
+
+        fn legal_actions(&self) -> Vec<Toss> {
+            vec![Toss(true), Toss(false)]
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<EndlessOutcome> {
+            self.heads.map(|_| EndlessOutcome { moves: 1 })
+        }
+
+        fn chance_outcomes(&self) -> Option<Vec<(Toss, f64)>> {
+            match self.heads {
+                None => Some(vec![(Toss(true), 0.75), (Toss(false), 0.25)]),
+                Some(_) => None,
+            }
+        }
+    }
+
+    impl Game for CoinGame {
+        type GameState = CoinState;
+        type GameAction = Toss;
+        type GameOutcome = EndlessOutcome;
+    }
+
+    #[test]
+    fn chance_children_are_sampled_by_probability() {
+        let mut mcts = Mcts::<CoinGame>::default().with_seed(3);
+
+        mcts.search(CoinState::default(), 4_001);
+
+        let root = mcts.root().unwrap();
+        assert_eq!(NodeKind::Chance, root.data().kind());
+
+        let stats = mcts.root_statistics();
+        assert_eq!(
+            vec![0.75, 0.25],
+            root.children()
+                .iter()
+                .map(|c| c.data().prior())
+                .collect::<Vec<_>>()
+        );
+        let heads_share = stats[0].visits as f64 / 4_000.0;
+        assert!(
+            (heads_share - 0.75).abs() < 0.03,
+            "heads share {}",
+            heads_share
+        );
+    }
+
+    #[test]
+    fn progressive_widening_grows_children_with_visits() {
+        for &iterations in &[1, 2, 3, 10, 26, 50] {
+            let mut mcts = Mcts::<WideGame>::default()
+                .with_seed(0)
+                .with_progressive_widening(1.0, 0.5);
+
+            mcts.search(WideState(0), iterations);
+
+            // Each iteration widens by the visits from the ones before it.
+            let visits_before_last = (iterations - 1).max(1) as f64;
+            let expected = visits_before_last.sqrt().ceil() as usize;
+            let root = mcts.root().unwrap();
+            assert_eq!(expected, root.child_count(), "{} iterations", iterations);
+        }
+    }
+
+    #[test]
+    fn progressive_widening_adds_actions_in_order() {
+        let mut mcts = Mcts::<WideGame>::default()
+            .with_seed(0)
+            .with_progressive_widening(1.0, 0.5);
+
+        mcts.search(WideState(0), 10);
+
+        let actions: Vec<usize> = mcts.root_statistics().iter().map(|s| s.action.0).collect();
+        assert_eq!(vec![0, 1, 2], actions);
+    }
+
+    #[test]
+    fn incremental_expansion_adds_one_child_per_visit() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_seed(2)
+            .with_incremental_expansion();
+        let root = Node::new(NodeData::root());
+        let state = SimpleGameState::new();
+
+        let counts: Vec<usize> = (0..5)
+            .map(|_| {
+                mcts.iterate(&root, &state);
+                root.child_count()
+            })
+            .collect();
+
+        assert_eq!(vec![1, 2, 3, 3, 3], counts);
+        assert!(root.data().untried.borrow().is_empty());
+
+        for _ in 0..200 {
+            mcts.iterate(&root, &state);
+        }
+        assert!(root
+            .iter_preorder()
+            .all(|n| n.child_count() <= n.data().visits() as usize));
+    }
+
+    #[test]
+    fn without_widening_every_action_is_expanded() {
+        let mut mcts = Mcts::<WideGame>::default().with_seed(0);
+
+        mcts.search(WideState(0), 2);
+
+        assert_eq!(50, mcts.root().unwrap().child_count());
+    }
+
+    #[test]
+    fn evaluator_leaves_children_unvisited_on_expansion() {
+        let mut mcts = Mcts::new(simple_reward).with_evaluator(Box::new(ConstantEvaluator(0.5)));
+
+        mcts.search(SimpleGameState::new(), 1);
+
+        let root = mcts.root().unwrap();
+        assert_eq!(1, root.data().visits());
+        assert!(root.children().iter().all(|c| c.data().visits() == 0));
+    }
+
+    impl RolloutPolicy<SimpleGame> for FirstActionRollout {
+        fn rollout(&self, state: &SimpleGameState, _: &mut StdRng) -> SimpleGameOutcome {
+            let mut state = state.clone();
+
+            loop {
+                if let Some(outcome) = state.outcome() {
+                    return outcome;
+                }
+
+                let action = state.legal_actions()[0];
+                state.make_next(action);
+            }
+        }
+    }
+
+    #[test]
+    fn deterministic_rollout_policy_is_used() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_rollout_policy(Box::new(FirstActionRollout));
+
+        // One iteration per root child: each is simulated exactly once.
+        // Bumping by 2 from 2 or 4 lands exactly on 42, but never from 3.
+        mcts.search(SimpleGameState::new(), 3);
+
+        let root = mcts.root().unwrap();
+        let rewards: Vec<f64> = root
+            .children()
+            .iter()
+            .map(|c| c.data().reward_sum())
+            .collect();
+
+        assert_eq!(vec![1.0, 0.0, 1.0], rewards);
+    }
+
+    #[test]
+    fn search_for_respects_budget() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+        let state = SimpleGameState::new();
+        let budget = Duration::from_millis(50);
+
+        let start = Instant::now();
+        let action = mcts.search_for(state.clone(), budget);
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= budget);
+        assert!(elapsed < budget * 10);
+
+        let legal: Vec<usize> = state.legal_actions().iter().map(|a| a.bump).collect();
+        assert!(legal.contains(&action.bump));
+        assert!(mcts.root().unwrap().data().visits() > 0);
+    }
+
+    #[test]
+    fn search_for_zero_budget_still_runs_an_iteration() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        mcts.search_for(SimpleGameState::new(), Duration::from_millis(0));
+
+        assert_eq!(1, mcts.root().unwrap().data().visits());
+    }
+
+    /// Hand-set stats, with mean rewards of 0.4, 0.6 and 0.9:
+    /// bump 2 and bump 3 tie on visits, and bump 4 has the best value.
+    fn searched_with_stats() -> Mcts<SimpleGame> {
+        let root = root_with_children(&[2, 3, 4]);
+        let stats = [(10, 4.0), (10, 6.0), (5, 4.5)];
+
+        for (child, &(visits, reward)) in root.children().iter().zip(stats.iter()) {
+            for _ in 0..visits {
+                child.data().record(reward / f64::from(visits));
+            }
+        }
+
+        let mut mcts = Mcts::new(simple_reward);
+        mcts.root = Some(root);
+        mcts
+    }
+
+    #[test]
+    fn best_action_before_search_is_none() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        assert!(mcts.best_action(FinalMoveSelection::MaxVisits).is_none());
+    }
+
+    #[test]
+    fn best_action_max_visits() {
+        let mcts = searched_with_stats();
+
+        let action = mcts.best_action(FinalMoveSelection::MaxVisits).unwrap();
+
+        assert_eq!(2, action.bump);
+    }
+
+    #[test]
+    fn best_action_max_value() {
+        let mcts = searched_with_stats();
+
+        let action = mcts.best_action(FinalMoveSelection::MaxValue).unwrap();
+
+        assert_eq!(4, action.bump);
+    }
+
+    #[test]
+    fn best_action_robust() {
+        let mcts = searched_with_stats();
+
+        let action = mcts.best_action(FinalMoveSelection::Robust).unwrap();
+
+        assert_eq!(3, action.bump);
+    }
+
+    #[test]
+    fn root_statistics_before_search_is_empty() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        assert!(mcts.root_statistics().is_empty());
+    }
+
+    #[test]
+    fn root_statistics_match_node_data() {
+        let root = root_with_children(&[2, 3]);
+
+        let first = root.nth_child(0).unwrap();
+        let first = first.data();
+        first.record(1.0);
+        first.record(0.0);
+        first.record(1.0);
+        first.record(0.5);
+
+        root.children()[1].data().record(0.0);
+
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+        mcts.root = Some(root);
+
+        let stats = mcts.root_statistics();
+
+        assert_eq!(2, stats.len());
+
+        assert_eq!(2, stats[0].action.bump);
+        assert_eq!(4, stats[0].visits);
+        assert_eq!(0.625, stats[0].mean_reward);
+        assert_eq!(0.5, stats[0].win_rate);
+
+        assert_eq!(3, stats[1].action.bump);
+        assert_eq!(1, stats[1].visits);
+        assert_eq!(0.0, stats[1].mean_reward);
+        assert_eq!(0.0, stats[1].win_rate);
+    }
+
+    /// Priors 0.7, 0.2 and 0.1 with mean rewards 0.2, 0.6 and unvisited.
+    fn root_with_priors() -> Node<NodeData<SimpleGame>> {
+        let mut root = Node::new(NodeData::root());
+        let priors = vec![
+            (SimpleGameAction::new(2), 0.7),
+            (SimpleGameAction::new(3), 0.2),
+            (SimpleGameAction::new(4), 0.1),
+        ];
+        expand_with_priors(&mut root, &SimpleGameState::new(), priors);
+
+        for _ in 0..5 {
+            root.children()[0].data().record(0.2);
+            root.children()[1].data().record(0.6);
+        }
+        for _ in 0..10 {
+            root.data().record(0.0);
+        }
+
+        root
+    }
+
+    #[test]
+    fn puct_known_value() {
+        let score = puct(0.2, 0.7, 5, 10, 1.0);
+
+        let expected = 0.2 + 0.7 * 10f64.sqrt() / 6.0;
+        assert!((score - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn expand_with_priors_sets_child_priors() {
+        let root = root_with_priors();
+
+        let priors: Vec<f64> = root.children().iter().map(|c| c.data().prior()).collect();
+
+        assert_eq!(vec![0.7, 0.2, 0.1], priors);
+        assert!(root
+            .children()
+            .iter()
+            .all(|c| c.data().player() == Some(PlayerColor::Black.into())));
+    }
+
+    #[test]
+    fn puct_low_c_prefers_value() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward).with_c_puct(1.0);
+        let root = root_with_priors();
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(3, selected.data().action().unwrap().bump);
+    }
+
+    #[test]
+    fn puct_high_c_prefers_prior() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward).with_c_puct(5.0);
+        let root = root_with_priors();
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(2, selected.data().action().unwrap().bump);
+    }
+
+    fn noised_priors(seed: u64) -> Vec<f64> {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward).with_seed(seed);
+        mcts.root = Some(root_with_priors());
+
+        mcts.add_root_dirichlet_noise(0.3, 0.25);
+
+        let root = mcts.root().unwrap();
+        let priors = root.children().iter().map(|c| c.data().prior()).collect();
+        priors
+    }
+
+    #[test]
+    fn dirichlet_noise_keeps_priors_normalized() {
+        let priors = noised_priors(7);
+
+        let total: f64 = priors.iter().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_ne!(vec![0.7, 0.2, 0.1], priors);
+    }
+
+    #[test]
+    fn dirichlet_noise_is_deterministic_for_a_seed() {
+        assert_eq!(noised_priors(7), noised_priors(7));
+        assert_ne!(noised_priors(7), noised_priors(8));
+    }
+
+    #[test]
+    fn dirichlet_noise_without_root_does_nothing() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        mcts.add_root_dirichlet_noise(0.3, 0.25);
+
+        assert!(mcts.root().is_none());
+    }
+
+    /// Visit counts 10, 30 and 60 for bumps 2, 3 and 4.
+    fn searched_with_visits() -> Mcts<SimpleGame> {
+        let root = root_with_children(&[2, 3, 4]);
+
+        for (child, &visits) in root.children().iter().zip([10, 30, 60].iter()) {
+            for _ in 0..visits {
+                child.data().record(0.5);
+            }
+        }
+
+        let mut mcts = Mcts::new(simple_reward);
+        mcts.root = Some(root);
+        mcts
+    }
+
+    #[test]
+    fn dense_root_visits_follow_action_indices() {
+        assert_eq!(vec![10, 30, 60], searched_with_visits().dense_root_visits());
+        assert_eq!(
+            vec![0, 0, 0],
+            Mcts::<SimpleGame>::new(simple_reward).dense_root_visits()
+        );
+    }
+
+    #[test]
+    fn sample_action_temperature_zero_is_argmax() {
+        let mut mcts = searched_with_visits().with_seed(1);
+
+        for _ in 0..20 {
+            assert_eq!(4, mcts.sample_action(0.0).bump);
+        }
+    }
+
+    #[test]
+    fn sample_action_temperature_one_is_proportional_to_visits() {
+        let mut mcts = searched_with_visits().with_seed(1);
+        let mut counts = [0; 3];
+
+        for _ in 0..10_000 {
+            counts[mcts.sample_action(1.0).bump - 2] += 1;
+        }
+
+        let expected = [0.1, 0.3, 0.6];
+        for (&count, &p) in counts.iter().zip(expected.iter()) {
+            let observed = f64::from(count) / 10_000.0;
+            assert!((observed - p).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn sample_action_is_deterministic_for_a_seed() {
+        let sample = |seed| {
+            let mut mcts = searched_with_visits().with_seed(seed);
+            (0..10)
+                .map(|_| mcts.sample_action(1.0).bump)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sample(3), sample(3));
+    }
+
+    #[test]
+    fn advance_root_keeps_child_statistics() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        let action = mcts.search(SimpleGameState::new(), 300);
+        let child = mcts
+            .root()
+            .unwrap()
+            .children()
+            .iter()
+            .find(|c| c.data().action() == Some(action))
+            .cloned()
+            .unwrap();
+        let child_visits = child.data().visits();
+        let grandchildren = child.child_count();
+
+        mcts.advance_root(action);
+
+        let root = mcts.root().unwrap();
+        assert!(root.is_root());
+        assert!(root.ptr_eq(&child));
+        assert_eq!(child_visits, root.data().visits());
+        assert_eq!(grandchildren, root.child_count());
+
+        let mut state = SimpleGameState::new();
+        state.make_next(action);
+        mcts.search(state, 100);
+
+        assert_eq!(child_visits + 100, mcts.root().unwrap().data().visits());
+    }
+
+    #[test]
+    fn advance_root_to_unexpanded_action_starts_fresh() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        mcts.search(SimpleGameState::new(), 10);
+        mcts.advance_root(SimpleGameAction::new(1));
+
+        assert!(mcts.root().is_none());
+
+        let mut state = SimpleGameState::new();
+        state.make_next(SimpleGameAction::new(1));
+        mcts.search(state, 1);
+
+        assert_eq!(1, mcts.root().unwrap().data().visits());
+    }
+
+    #[test]
+    fn search_without_advance_root_starts_fresh() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        mcts.search(SimpleGameState::new(), 50);
+        mcts.search(SimpleGameState::new(), 20);
+
+        assert_eq!(20, mcts.root().unwrap().data().visits());
+    }
+
+    fn state_at(num: usize) -> SimpleGameState {
+        let mut state = SimpleGameState::new();
+        state.num = num;
+        state
+    }
+
+    #[test]
+    fn solver_proves_forced_win_at_root() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        // From 38, bumping by 4 lands exactly on 42.
+        let action = mcts.search(state_at(38), 50);
+
+        let root = mcts.root().unwrap();
+        assert_eq!(ProofStatus::ProvenWin, root.data().proof_status());
+        assert_eq!(4, action.bump);
+    }
+
+    #[test]
+    fn solver_proves_forced_loss_at_root() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        // From 41, every bump overshoots 42.
+        mcts.search(state_at(41), 10);
+
+        let root = mcts.root().unwrap();
+        assert_eq!(ProofStatus::ProvenLoss, root.data().proof_status());
+        assert!(root
+            .children()
+            .iter()
+            .all(|c| c.data().proof_status() == ProofStatus::ProvenLoss));
+    }
+
+    #[test]
+    fn solver_selection_avoids_proven_losses() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        // From 39, bumping by 3 wins, by 4 overshoots, and by 2 reaches 41, a proven loss.
+        let action = mcts.search(state_at(39), 200);
+
+        assert_eq!(3, action.bump);
+        let stats = mcts.root_statistics();
+        let losing_visits: u32 = stats
+            .iter()
+            .filter(|s| s.action.bump != 3)
+            .map(|s| s.visits)
+            .sum();
+        assert!(losing_visits < 10);
+    }
+
+    #[test]
+    fn same_seed_searches_are_identical() {
+        let search = |seed| {
+            let mut mcts = Mcts::<SimpleGame>::new(simple_reward).with_seed(seed);
+            let action = mcts.search(SimpleGameState::new(), 500);
+            let visits: Vec<u32> = mcts
+                .root()
+                .unwrap()
+                .iter_preorder()
+                .map(|n| n.data().visits())
+                .collect();
+
+            (action, visits)
+        };
+
+        let (first_action, first_visits) = search(7);
+        let (second_action, second_visits) = search(7);
+
+        assert_eq!(first_action, second_action);
+        assert_eq!(first_visits, second_visits);
+    }
+
+    #[test]
+    fn rave_beta_known_values() {
+        assert_eq!(0.0, rave_beta(5, 0, 0.1));
+        assert_eq!(1.0, rave_beta(0, 5, 0.1));
+        assert!((rave_beta(10, 30, 0.01) - 30.0 / 43.0).abs() < 1e-12);
+    }
+
+    /// Always bumps by 3, recording what it plays.
+    struct BumpThreeRollout;
+
+    impl RolloutPolicy<SimpleGame> for BumpThreeRollout {
+        fn rollout(&self, state: &SimpleGameState, rng: &mut StdRng) -> SimpleGameOutcome {
+            self.rollout_recording(state, rng, &mut Vec::new())
+        }
+
+        fn rollout_recording(
+            &self,
+            state: &SimpleGameState,
+            _: &mut StdRng,
+            played: &mut Vec<(PlayerId, SimpleGameAction)>,
+        ) -> SimpleGameOutcome {
+            let mut state = state.clone();
+
+            loop {
+                if let Some(outcome) = state.outcome() {
+                    return outcome;
+                }
+
+                let action = SimpleGameAction::new(3);
+                played.push((state.current_player_turn(), action));
+                state.make_next(action);
+            }
+        }
+    }
+
+    #[test]
+    fn amaf_counts_accumulate_for_rollout_actions() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_rave(0.0)
+            .with_rollout_policy(Box::new(BumpThreeRollout));
+
+        // The first iteration walks into the first child, bumping by 2,
+        // then the rollout only ever bumps by 3.
+        mcts.search(SimpleGameState::new(), 1);
+
+        let root = mcts.root().unwrap();
+        let children = root.children();
+        let amaf: Vec<u32> = children.iter().map(|c| c.data().amaf_visits()).collect();
+        let visits: Vec<u32> = children.iter().map(|c| c.data().visits()).collect();
+
+        assert_eq!(vec![1, 1, 0], amaf);
+        assert_eq!(vec![1, 0, 0], visits);
+    }
+
+    #[test]
+    fn amaf_is_not_tracked_without_rave() {
+        let mut mcts =
+            Mcts::<SimpleGame>::new(simple_reward).with_rollout_policy(Box::new(BumpThreeRollout));
+
+        mcts.search(SimpleGameState::new(), 1);
+
+        let root = mcts.root().unwrap();
+        assert!(root.children().iter().all(|c| c.data().amaf_visits() == 0));
+    }
+
+    #[test]
+    fn rave_selection_uses_blended_score() {
+        let root = root_with_children(&[2, 3]);
+        let (weak, strong) = (root.nth_child(0).unwrap(), root.nth_child(1).unwrap());
+
+        for i in 0..10 {
+            root.data().record(0.0);
+            weak.data().record(if i % 2 == 0 { 1.0 } else { 0.0 });
+            strong.data().record(0.6);
+        }
+        for i in 0..100 {
+            weak.data().record_amaf(if i < 90 { 1.0 } else { 0.0 });
+        }
+
+        let plain = Mcts::<SimpleGame>::new(simple_reward).with_exploration(0.0);
+        let rave = Mcts::<SimpleGame>::new(simple_reward)
+            .with_exploration(0.0)
+            .with_rave(0.0);
+
+        assert_eq!(
+            3,
+            plain
+                .select_child(&root)
+                .unwrap()
+                .data()
+                .action()
+                .unwrap()
+                .bump
+        );
+        assert_eq!(
+            2,
+            rave.select_child(&root)
+                .unwrap()
+                .data()
+                .action()
+                .unwrap()
+                .bump
+        );
+    }
+
+    #[test]
+    fn transposed_states_share_statistics() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_seed(3)
+            .with_transpositions();
+        let state = SimpleGameState::new();
+        let root = Node::new(NodeData::root());
+
+        for _ in 0..200 {
+            mcts.iterate(&root, &state);
+        }
+
+        // Bumping by 2 then 3, or by 3 then 2, both reach 5.
+        let via_two = root.nth_child(0).unwrap();
+        let via_three = root.nth_child(1).unwrap();
+        let five_via_two = via_two.nth_child(1).unwrap();
+        let five_via_three = via_three.nth_child(0).unwrap();
+
+        assert!(Rc::ptr_eq(
+            &five_via_two.data().stats,
+            &five_via_three.data().stats,
+        ));
+
+        // 2 and 3 are each only reachable one way, so an iteration that visits 5
+        // went through whichever of them it also visited.
+        let five_before = five_via_two.data().visits();
+        let (mut through_two, mut through_three) = (0, 0);
+        for _ in 0..1_000 {
+            let counts = |n: &Node<NodeData<SimpleGame>>| n.data().visits();
+            let (two, three, five) = (counts(&via_two), counts(&via_three), counts(&five_via_two));
+
+            mcts.iterate(&root, &state);
+
+            if counts(&five_via_two) > five {
+                if counts(&via_two) > two {
+                    through_two += 1;
+                }
+                if counts(&via_three) > three {
+                    through_three += 1;
+                }
+            }
+        }
+
+        assert!(through_two > 0);
+        assert!(through_three > 0);
+        assert_eq!(
+            five_before + through_two + through_three,
+            five_via_three.data().visits()
+        );
+    }
+
+    #[test]
+    fn fresh_search_clears_transpositions() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward).with_transpositions();
+
+        mcts.search(SimpleGameState::new(), 200);
+        mcts.search(SimpleGameState::new(), 20);
+
+        assert_eq!(20, mcts.root().unwrap().data().visits());
+        assert!(mcts.transpositions().unwrap().len() < 60);
+    }
+
+    #[test]
+    fn search_parallel_merges_per_thread_visits() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward).with_seed(11);
+        let state = SimpleGameState::new();
+
+        let merged = mcts.parallel_root_visits(state.clone(), 100, 4);
+
+        // Replaying each worker's seeded search serially gives the same trees.
+        let config = mcts.config();
+        let mut expected = vec![0; 3];
+        for seed in mcts.worker_seeds(4) {
+            let mut worker = config.build(seed);
+            worker.search(state.clone(), 100);
+            for (i, (_, visits)) in worker.root_visits().into_iter().enumerate() {
+                expected[i] += visits;
+            }
+        }
+
+        let merged_visits: Vec<u32> = merged.iter().map(|&(_, v)| v).collect();
+        assert_eq!(expected, merged_visits);
+        assert_eq!(400, merged_visits.iter().sum::<u32>());
+
+        let best = first_max_by(merged.iter(), |(_, v)| *v).unwrap().0;
+        assert_eq!(best, mcts.search_parallel(state, 100, 4));
+    }
+
+    #[test]
+    fn worker_seeds_are_distinct() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        let mut seeds = mcts.worker_seeds(8);
+        seeds.sort_unstable();
+        seeds.dedup();
+
+        assert_eq!(8, seeds.len());
+    }
+
+    #[test]
+    fn principal_variation_before_search_is_empty() {
+        let mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        assert!(mcts.principal_variation().is_empty());
+    }
+
+    #[test]
+    fn principal_variation_follows_most_visited_children() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward).with_seed(5);
+
+        mcts.search(SimpleGameState::new(), 500);
+        let pv = mcts.principal_variation();
+
+        assert!(pv.len() > 1);
+        assert_eq!(Some(pv[0]), mcts.best_action(FinalMoveSelection::MaxVisits));
+
+        let mut node = mcts.root().unwrap().clone();
+        for action in pv {
+            let child = node
+                .children()
+                .iter()
+                .find(|c| c.data().action() == Some(action))
+                .cloned()
+                .unwrap();
+            let most_visits = node.children().iter().map(|c| c.data().visits()).max();
+
+            assert_eq!(most_visits, Some(child.data().visits()));
+            node = child;
+        }
+
+        assert!(node.children().iter().all(|c| c.data().visits() == 0));
+    }
+
+    #[test]
+    fn principal_variation_stops_at_terminal_node() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        // From 38, bumping by 4 wins at once, so the line ends there.
+        mcts.search(state_at(38), 50);
+
+        let pv = mcts.principal_variation();
+        assert_eq!(vec![4], pv.iter().map(|a| a.bump).collect::<Vec<_>>());
+    }
+
+    struct FirstChildPolicy;
+
+    impl SelectionPolicy<SimpleGame> for FirstChildPolicy {
+        fn select(&self, _: &Node<NodeData<SimpleGame>>) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn search_honors_custom_selection_policy() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_selection_policy(Box::new(FirstChildPolicy));
+
+        let action = mcts.search(SimpleGameState::new(), 50);
+        let visits: Vec<u32> = mcts
+            .root_statistics()
+            .iter()
+            .map(|stat| stat.visits)
+            .collect();
+
+        assert_eq!(2, action.bump);
+        assert_eq!(vec![50, 0, 0], visits);
+    }
+
+    #[test]
+    fn ucb1_policy_skips_proven_losses() {
+        let root = root_with_children(&[2, 3]);
+        root.nth_child(0)
+            .unwrap()
+            .data()
+            .proof
+            .set(ProofStatus::ProvenLoss);
+
+        assert_eq!(1, SelectionPolicy::select(&Ucb1Policy::default(), &root));
+    }
+
+    #[test]
+    fn max_rollout_depth_keeps_endless_games_searchable() {
+        use crate::tests::{count_moves, EndlessGame, EndlessOutcome, EndlessState};
+
+        let mut mcts = Mcts::<EndlessGame>::new(|outcome: &EndlessOutcome, _| {
+            1.0 / (1.0 + outcome.moves as f64)
+        })
+        .with_max_rollout_depth(10, count_moves);
+
+        mcts.search(EndlessState::default(), 100);
+
+        assert_eq!(100, mcts.root().unwrap().data().visits());
+    }
+
+    /// A root whose first child has won 9 of 10 visits, and whose other children are unvisited.
+    fn root_with_strong_child() -> Node<NodeData<SimpleGame>> {
+        let root = root_with_children(&[2, 3, 4]);
+
+        for i in 0..10 {
+            root.data().record(0.0);
+            root.children()[0]
+                .data()
+                .record(if i == 0 { 0.0 } else { 1.0 });
+        }
+
+        root
+    }
+
+    #[test]
+    fn infinite_fpu_tries_unvisited_children_first() {
+        let root = root_with_strong_child();
+        let mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(3, selected.data().action().unwrap().bump);
+    }
+
+    #[test]
+    fn small_fpu_exploits_strong_visited_child() {
+        let root = root_with_strong_child();
+        let mcts = Mcts::<SimpleGame>::new(simple_reward).with_fpu(0.5);
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(2, selected.data().action().unwrap().bump);
+    }
+
+    #[test]
+    fn fpu_above_visited_score_prefers_unvisited() {
+        let root = root_with_strong_child();
+        let mcts = Mcts::<SimpleGame>::new(simple_reward)
+            .with_exploration(0.0)
+            .with_fpu(0.95);
+
+        let selected = mcts.select_child(&root).unwrap();
+
+        assert_eq!(3, selected.data().action().unwrap().bump);
+    }
+
+    #[test]
+    fn ucb1_tuned_known_value_with_capped_variance() {
+        // Rewards 1, 0, 1, 1 from a parent visited 10 times.
+        let score = ucb1_tuned(3.0, 3.0, 4, 10);
+
+        assert!((score - 1.129_357).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ucb1_tuned_known_value_with_low_variance() {
+        // A reward of 0.5 on every one of 1000 visits.
+        let score = ucb1_tuned(500.0, 250.0, 1_000, 1_000);
+
+        assert!((score - 0.528_494).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ucb1_tuned_unvisited_is_infinite() {
+        assert_eq!(f64::INFINITY, ucb1_tuned(0.0, 0.0, 0, 10));
+    }
+
+    #[test]
+    fn record_accumulates_squared_rewards() {
+        let data = NodeData::<SimpleGame>::root();
+
+        data.record(0.5);
+        data.record(1.0);
+
+        assert_eq!(1.25, data.reward_sq_sum());
+    }
+
+    #[test]
+    fn ucb1_tuned_policy_prefers_consistent_child() {
+        let root = root_with_children(&[2, 3]);
+        let (erratic, steady) = (root.nth_child(0).unwrap(), root.nth_child(1).unwrap());
+
+        // Equal means, but the first child's rewards vary wildly.
+        for i in 0..20 {
+            root.data().record(0.0);
+            erratic.data().record(if i % 2 == 0 { 1.0 } else { 0.0 });
+            steady.data().record(0.5);
+        }
+
+        let mcts =
+            Mcts::<SimpleGame>::new(simple_reward).with_selection_policy(Box::new(Ucb1TunedPolicy));
+
+        assert_eq!(
+            2,
+            mcts.select_child(&root)
+                .unwrap()
+                .data()
+                .action()
+                .unwrap()
+                .bump
+        );
+    }
+
+    #[test]
+    fn self_play_records_every_move_with_the_final_outcome() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward).with_seed(2);
+
+        let samples = mcts.self_play(SimpleGameState::new(), 50);
+
+        let mut state = SimpleGameState::new();
+        for sample in &samples {
+            assert_eq!(state.num, sample.state.num);
+            assert!(state.outcome().is_none());
+
+            let total: f64 = sample.policy.iter().map(|(_, p)| p).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+
+            state.make_next(sample.action);
+        }
+
+        let outcome = state.outcome().unwrap();
+        assert!(!samples.is_empty());
+        assert!(samples.iter().all(|s| s.outcome == outcome));
+    }
+
+    #[test]
+    fn analyze_ranks_every_legal_action_by_visits() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward).with_seed(4);
+        let state = SimpleGameState::new();
+
+        let analysis = mcts.analyze(state.clone(), 300);
+
+        assert!(analysis.windows(2).all(|w| w[0].visits >= w[1].visits));
+
+        let mut bumps: Vec<usize> = analysis.iter().map(|m| m.action.bump).collect();
+        bumps.sort_unstable();
+        let mut legal: Vec<usize> = state.legal_actions().iter().map(|a| a.bump).collect();
+        legal.sort_unstable();
+        assert_eq!(legal, bumps);
+
+        assert_eq!(300, analysis.iter().map(|m| m.visits).sum::<u32>());
+        assert_eq!(
+            Some(analysis[0].action),
+            mcts.best_action(FinalMoveSelection::MaxVisits)
+        );
+    }
+
+    #[test]
+    fn analyze_terminal_state_is_empty() {
+        let mut mcts = Mcts::<SimpleGame>::new(simple_reward);
+
+        assert!(mcts.analyze(state_at(42), 10).is_empty());
+    }
+
+    #[test]
+    fn default_search_prefers_a_win_over_a_draw() {
+        use crate::tests::{DrawAction, DrawGame, DrawState};
+
+        let mut mcts = Mcts::<DrawGame>::default();
+
+        assert_eq!(DrawAction::Win, mcts.search(DrawState::default(), 50));
+
+        let drawn = &mcts.root_statistics()[0];
+        assert_eq!(DrawAction::Draw, drawn.action);
+        assert_eq!(0.5, drawn.mean_reward);
+    }
+}
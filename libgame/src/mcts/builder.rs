@@ -0,0 +1,171 @@
+use super::{Mcts, Reward, RewardFn, RolloutPolicy, SelectionPolicy, StateEvaluator};
+use crate::Game;
+use std::sync::Arc;
+
+/// Collects an `Mcts`'s settings before building it, as an alternative
+/// to chaining `Mcts`'s own `with_` methods onto a constructor.
+/// Any setting left unset takes the same default as `Mcts::new`:
+/// - reward: `winner_reward`, i.e. `GameOutcome::value_for`
+/// - exploration: sqrt(2)
+/// - fpu: infinity, so every child is tried once before any is revisited
+/// - discount: 1.0, i.e. undiscounted
+/// - seed: none, so the search is seeded from entropy
+/// - rollout policy: `RandomRollout`
+/// - selection policy: `Ucb1Policy`, with the exploration and fpu above
+/// - evaluator: none, so leaves are scored by rollouts
+pub struct MctsBuilder<G: Game> {
+    reward: Option<RewardFn<G>>,
+    exploration: Option<f64>,
+    fpu: Option<f64>,
+    discount: Option<f64>,
+    seed: Option<u64>,
+    rollout: Option<Box<dyn RolloutPolicy<G> + Send + Sync>>,
+    selection: Option<Box<dyn SelectionPolicy<G> + Send + Sync>>,
+    evaluator: Option<Box<dyn StateEvaluator<G> + Send + Sync>>,
+}
+
+impl<G: Game> MctsBuilder<G> {
+    pub fn new() -> Self {
+        Self {
+            reward: None,
+            exploration: None,
+            fpu: None,
+            discount: None,
+            seed: None,
+            rollout: None,
+            selection: None,
+            evaluator: None,
+        }
+    }
+
+    /// See `Mcts::new`.
+    pub fn reward(mut self, reward: impl Reward<G> + Send + Sync + 'static) -> Self {
+        self.reward = Some(Arc::new(reward));
+        self
+    }
+
+    /// See `Mcts::with_exploration`.
+    pub fn exploration(mut self, c: f64) -> Self {
+        self.exploration = Some(c);
+        self
+    }
+
+    /// See `Mcts::with_fpu`.
+    pub fn fpu(mut self, fpu: f64) -> Self {
+        self.fpu = Some(fpu);
+        self
+    }
+
+    /// See `Mcts::with_discount`.
+    pub fn discount(mut self, discount: f64) -> Self {
+        self.discount = Some(discount);
+        self
+    }
+
+    /// See `Mcts::with_seed`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// See `Mcts::with_rollout_policy`.
+    pub fn rollout_policy(mut self, rollout: Box<dyn RolloutPolicy<G> + Send + Sync>) -> Self {
+        self.rollout = Some(rollout);
+        self
+    }
+
+    /// See `Mcts::with_selection_policy`.
+    pub fn selection_policy(
+        mut self,
+        selection: Box<dyn SelectionPolicy<G> + Send + Sync>,
+    ) -> Self {
+        self.selection = Some(selection);
+        self
+    }
+
+    /// See `Mcts::with_evaluator`.
+    pub fn evaluator(mut self, evaluator: Box<dyn StateEvaluator<G> + Send + Sync>) -> Self {
+        self.evaluator = Some(evaluator);
+        self
+    }
+
+    pub fn build(self) -> Mcts<G> {
+        let mut mcts = match self.reward {
+            Some(reward) => Mcts::with_reward_fn(reward),
+            None => Mcts::default(),
+        };
+
+        if let Some(c) = self.exploration {
+            mcts = mcts.with_exploration(c);
+        }
+        if let Some(fpu) = self.fpu {
+            mcts = mcts.with_fpu(fpu);
+        }
+        if let Some(discount) = self.discount {
+            mcts = mcts.with_discount(discount);
+        }
+        if let Some(seed) = self.seed {
+            mcts = mcts.with_seed(seed);
+        }
+        if let Some(rollout) = self.rollout {
+            mcts = mcts.with_rollout_policy(rollout);
+        }
+        if let Some(selection) = self.selection {
+            mcts = mcts.with_selection_policy(selection);
+        }
+        if let Some(evaluator) = self.evaluator {
+            mcts = mcts.with_evaluator(evaluator);
+        }
+
+        mcts
+    }
+}
+
+impl<G: Game> Default for MctsBuilder<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{SimpleGame, SimpleGameState};
+
+    fn tree_visits(mcts: &mut Mcts<SimpleGame>) -> Vec<u32> {
+        mcts.search(SimpleGameState::new(), 300);
+
+        mcts.root()
+            .unwrap()
+            .iter_preorder()
+            .map(|n| n.data().visits())
+            .collect()
+    }
+
+    #[test]
+    fn built_settings_match_with_methods() {
+        let mut built = Mcts::<SimpleGame>::builder()
+            .seed(7)
+            .exploration(0.2)
+            .discount(0.9)
+            .build();
+        let mut chained = Mcts::<SimpleGame>::default()
+            .with_seed(7)
+            .with_exploration(0.2)
+            .with_discount(0.9);
+        let mut seeded_only = Mcts::<SimpleGame>::default().with_seed(7);
+
+        let built_visits = tree_visits(&mut built);
+
+        assert_eq!(tree_visits(&mut chained), built_visits);
+        assert_ne!(tree_visits(&mut seeded_only), built_visits);
+    }
+
+    #[test]
+    fn unset_fields_take_mcts_defaults() {
+        let mut built = MctsBuilder::<SimpleGame>::new().seed(3).build();
+        let mut default = Mcts::<SimpleGame>::default().with_seed(3);
+
+        assert_eq!(tree_visits(&mut default), tree_visits(&mut built));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::Game;
+
+/// Estimates the value of a state directly, such as with a learned value function,
+/// in place of simulating the rest of the game with a rollout.
+pub trait StateEvaluator<G: Game> {
+    /// The value of `state` to the player whose turn it is,
+    /// on the same scale as the search's rewards: from 0 for a certain loss
+    /// to 1 for a certain win.
+    fn evaluate(&self, state: &G::GameState) -> f64;
+}
+
+/// The priors of a state's legal actions and the state's value,
+/// as returned by a `PolicyValueNet`.
+pub type Evaluation<G> = (Vec<(<G as Game>::GameAction, f64)>, f64);
+
+/// Evaluates a state's move priors and value in one call,
+/// as the policy and value heads of an AlphaZero-style network do.
+pub trait PolicyValueNet<G: Game> {
+    /// Each legal action in `state` with its prior probability,
+    /// and the value of `state` to the player whose turn it is,
+    /// on the same scale as `StateEvaluator::evaluate`.
+    fn evaluate(&self, state: &G::GameState) -> Evaluation<G>;
+
+    /// Evaluates several states at once, returning their evaluations in order,
+    /// as used by `Mcts::search_batched`.
+    /// Override this to run one batched inference rather than one per state.
+    fn evaluate_batch(&self, states: &[G::GameState]) -> Vec<Evaluation<G>> {
+        states.iter().map(|s| self.evaluate(s)).collect()
+    }
+}
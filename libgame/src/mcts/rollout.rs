@@ -0,0 +1,322 @@
+use crate::{Game, GameOutcome, GameState, PlayerId};
+use rand::distributions::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Decides how the simulation phase plays out a game
+/// from a newly-reached state to its final outcome.
+/// Any randomness should come from `rng`, the searcher's own generator,
+/// so that seeded searches are reproducible.
+pub trait RolloutPolicy<G: Game> {
+    fn rollout(&self, state: &G::GameState, rng: &mut StdRng) -> G::GameOutcome;
+
+    /// Like `rollout`, but also appends each action played, with the player who played it,
+    /// to `played`, so that RAVE can credit them.
+    /// The default records nothing, leaving RAVE to learn only from the tree path.
+    fn rollout_recording(
+        &self,
+        state: &G::GameState,
+        rng: &mut StdRng,
+        played: &mut Vec<(PlayerId, G::GameAction)>,
+    ) -> G::GameOutcome {
+        let _ = played;
+        self.rollout(state, rng)
+    }
+
+    /// The action this policy plays in `state`, a single ply of a rollout, out of `actions`.
+    /// Used when the policy's plies are interleaved with another player's,
+    /// as with `Mcts::with_opponent_model`, so that a policy which plays whole
+    /// rollouts at once should override this to play the same way.
+    /// Returns `None` only if `actions` is empty. Defaults to a uniformly random action.
+    fn choose_action(
+        &self,
+        state: &G::GameState,
+        actions: &[G::GameAction],
+        rng: &mut StdRng,
+    ) -> Option<G::GameAction> {
+        choose_randomly::<G>(state, actions, rng)
+    }
+
+    /// Whether a rollout should stop at `state`, `depth` moves in, without the game having ended,
+    /// and if so, the outcome to score it with instead.
+    /// Consulted alongside `choose_action`. Defaults to never stopping early.
+    fn cutoff(&self, state: &G::GameState, depth: usize) -> Option<G::GameOutcome> {
+        let _ = (state, depth);
+        None
+    }
+}
+
+/// Plays uniformly random legal actions until the game ends.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct RandomRollout;
+
+impl<G: Game> RolloutPolicy<G> for RandomRollout {
+    fn rollout(&self, state: &G::GameState, rng: &mut StdRng) -> G::GameOutcome {
+        RolloutPolicy::<G>::rollout_recording(self, state, rng, &mut Vec::new())
+    }
+
+    fn rollout_recording(
+        &self,
+        state: &G::GameState,
+        rng: &mut StdRng,
+        played: &mut Vec<(PlayerId, G::GameAction)>,
+    ) -> G::GameOutcome {
+        play_out::<G>(state, rng, played, choose_randomly::<G>, no_cutoff::<G>)
+    }
+}
+
+/// Plays out games like `RandomRollout`, except that a mover with an immediately
+/// winning action always takes it (a decisive move), and otherwise avoids actions
+/// after which the next player has an immediately winning reply (anti-decisive moves),
+/// choosing randomly among the rest. If every action allows such a reply,
+/// it chooses randomly among them all.
+/// Each move looks two plies ahead, so rollouts cost roughly the square
+/// of the branching factor more than random ones.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct DecisiveMoveRollout;
+
+impl<G: Game> RolloutPolicy<G> for DecisiveMoveRollout {
+    fn rollout(&self, state: &G::GameState, rng: &mut StdRng) -> G::GameOutcome {
+        RolloutPolicy::<G>::rollout_recording(self, state, rng, &mut Vec::new())
+    }
+
+    fn rollout_recording(
+        &self,
+        state: &G::GameState,
+        rng: &mut StdRng,
+        played: &mut Vec<(PlayerId, G::GameAction)>,
+    ) -> G::GameOutcome {
+        play_out::<G>(state, rng, played, choose_decisively::<G>, no_cutoff::<G>)
+    }
+
+    fn choose_action(
+        &self,
+        state: &G::GameState,
+        actions: &[G::GameAction],
+        rng: &mut StdRng,
+    ) -> Option<G::GameAction> {
+        choose_decisively::<G>(state, actions, rng)
+    }
+}
+
+/// Whether playing `action` in `state` immediately wins the game for its mover.
+fn wins_at_once<G: Game>(state: &G::GameState, action: G::GameAction) -> bool {
+    let mover = state.current_player_turn();
+    let mut next = state.clone();
+    next.make_next(action);
+
+    next.outcome().and_then(|o| o.winner()) == Some(mover)
+}
+
+/// Whether the opponent moving after `action` has an immediately winning reply.
+fn allows_winning_reply<G: Game>(state: &G::GameState, action: G::GameAction) -> bool {
+    let mut next = state.clone();
+    next.make_next(action);
+
+    if next.outcome().is_some() || next.current_player_turn() == state.current_player_turn() {
+        return false;
+    }
+
+    next.legal_actions()
+        .into_iter()
+        .any(|reply| wins_at_once::<G>(&next, reply))
+}
+
+fn choose_decisively<G: Game>(
+    state: &G::GameState,
+    actions: &[G::GameAction],
+    rng: &mut StdRng,
+) -> Option<G::GameAction> {
+    if let Some(&decisive) = actions.iter().find(|&&a| wins_at_once::<G>(state, a)) {
+        return Some(decisive);
+    }
+
+    let safe: Vec<G::GameAction> = actions
+        .iter()
+        .copied()
+        .filter(|&a| !allows_winning_reply::<G>(state, a))
+        .collect();
+
+    if safe.is_empty() {
+        actions.choose(rng).copied()
+    } else {
+        safe.choose(rng).copied()
+    }
+}
+
+fn choose_randomly<G: Game>(
+    _: &G::GameState,
+    actions: &[G::GameAction],
+    rng: &mut StdRng,
+) -> Option<G::GameAction> {
+    actions.choose(rng).copied()
+}
+
+fn no_cutoff<G: Game>(_: &G::GameState, _: usize) -> Option<G::GameOutcome> {
+    None
+}
+
+type Evaluator<G> = Box<dyn Fn(&<G as Game>::GameState) -> <G as Game>::GameOutcome + Send + Sync>;
+
+/// Plays uniformly random legal actions for at most `max_depth` moves.
+/// If the game has not ended by then, the evaluator estimates the outcome
+/// of the state reached instead, so games that may never end cannot hang the search.
+pub struct DepthLimitedRollout<G: Game> {
+    max_depth: usize,
+    evaluator: Evaluator<G>,
+}
+
+impl<G: Game> DepthLimitedRollout<G> {
+    pub fn new(
+        max_depth: usize,
+        evaluator: impl Fn(&G::GameState) -> G::GameOutcome + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            max_depth,
+            evaluator: Box::new(evaluator),
+        }
+    }
+}
+
+impl<G: Game> RolloutPolicy<G> for DepthLimitedRollout<G> {
+    fn rollout(&self, state: &G::GameState, rng: &mut StdRng) -> G::GameOutcome {
+        self.rollout_recording(state, rng, &mut Vec::new())
+    }
+
+    fn rollout_recording(
+        &self,
+        state: &G::GameState,
+        rng: &mut StdRng,
+        played: &mut Vec<(PlayerId, G::GameAction)>,
+    ) -> G::GameOutcome {
+        play_out::<G>(state, rng, played, choose_randomly::<G>, |s, depth| {
+            self.cutoff(s, depth)
+        })
+    }
+
+    fn cutoff(&self, state: &G::GameState, depth: usize) -> Option<G::GameOutcome> {
+        (depth >= self.max_depth).then(|| (self.evaluator)(state))
+    }
+}
+
+/// Plays out a copy of `state` with each player's action picked by `choose`
+/// from the legal actions, until the game ends or `cutoff` scores the state reached,
+/// returning the outcome either way. Chance events are sampled by their probabilities.
+pub(super) fn play_out<G: Game>(
+    state: &G::GameState,
+    rng: &mut StdRng,
+    played: &mut Vec<(PlayerId, G::GameAction)>,
+    choose: impl Fn(&G::GameState, &[G::GameAction], &mut StdRng) -> Option<G::GameAction>,
+    cutoff: impl Fn(&G::GameState, usize) -> Option<G::GameOutcome>,
+) -> G::GameOutcome {
+    let mut state = state.clone();
+    let mut depth = 0;
+    let mut actions = Vec::new();
+
+    loop {
+        if let Some(outcome) = state.outcome() {
+            return outcome;
+        }
+
+        if let Some(outcome) = cutoff(&state, depth) {
+            return outcome;
+        }
+
+        let action = match state.chance_outcomes() {
+            Some(events) => {
+                let weights = WeightedIndex::new(events.iter().map(|&(_, p)| p))
+                    .expect("Chance events must have positive total probability.");
+                events[rng.sample(weights)].0
+            }
+            None => {
+                state.legal_actions_into(&mut actions);
+                choose(&state, &actions, rng)
+                    .expect("A state without an outcome must have legal actions.")
+            }
+        };
+        played.push((state.current_player_turn(), action));
+        state.make_next(action);
+        depth += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        count_moves, EndlessGame, EndlessOutcome, EndlessState, SimpleGame, SimpleGameOutcome,
+        SimpleGameState,
+    };
+    use rand::SeedableRng;
+
+    #[test]
+    fn random_rollout_reaches_an_outcome() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let outcome =
+            RolloutPolicy::<SimpleGame>::rollout(&RandomRollout, &SimpleGameState::new(), &mut rng);
+
+        assert!(matches!(
+            outcome,
+            SimpleGameOutcome::BlackWins | SimpleGameOutcome::BothLose
+        ));
+    }
+
+    #[test]
+    fn random_rollout_records_actions_played() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut played = Vec::new();
+
+        RolloutPolicy::<SimpleGame>::rollout_recording(
+            &RandomRollout,
+            &SimpleGameState::new(),
+            &mut rng,
+            &mut played,
+        );
+
+        let total: usize = played.iter().map(|(_, a)| a.bump).sum();
+        assert!(total >= 42);
+        assert!(total - played.last().unwrap().1.bump < 42);
+    }
+
+    #[test]
+    fn decisive_rollout_always_takes_an_immediate_win() {
+        use crate::tests::{DrawGame, DrawOutcome, DrawState};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let outcome = RolloutPolicy::<DrawGame>::rollout(
+                &DecisiveMoveRollout,
+                &DrawState::default(),
+                &mut rng,
+            );
+
+            assert_eq!(DrawOutcome::BlackWins, outcome);
+        }
+    }
+
+    #[test]
+    fn depth_limited_rollout_stops_at_max_depth() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut played = Vec::new();
+        let policy = DepthLimitedRollout::<EndlessGame>::new(25, count_moves);
+
+        let outcome = policy.rollout_recording(&EndlessState::default(), &mut rng, &mut played);
+
+        assert_eq!(EndlessOutcome { moves: 25 }, outcome);
+        assert_eq!(25, played.len());
+    }
+
+    #[test]
+    fn depth_limited_rollout_returns_real_outcome_when_game_ends_first() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let policy = DepthLimitedRollout::<SimpleGame>::new(1_000, |_| panic!("not evaluated"));
+
+        let outcome = policy.rollout(&SimpleGameState::new(), &mut rng);
+
+        assert!(matches!(
+            outcome,
+            SimpleGameOutcome::BlackWins | SimpleGameOutcome::BothLose
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use super::{first_max_by, puct, rave_beta, ucb1, ucb1_tuned, NodeData, ProofStatus};
+use crate::Game;
+use tree::node::Node;
+
+/// Decides which child the selection phase descends into,
+/// which is all that distinguishes most MCTS variants from one another.
+pub trait SelectionPolicy<G: Game> {
+    /// The index of the chosen child of `parent`.
+    /// Only called on a node with at least one child.
+    fn select(&self, parent: &Node<NodeData<G>>) -> usize;
+}
+
+/// Selects the child maximizing UCB1.
+/// Unvisited children score `fpu` (first-play urgency) instead; at the default of infinity,
+/// they are all tried first, in order, while a finite value lets a child known to be
+/// strong be exploited before its siblings have been tried.
+/// With a RAVE bias, the mean reward in UCB1 is first blended
+/// with the AMAF mean reward, weighted by `rave_beta`.
+/// Children proven to lose are skipped unless every child is one.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ucb1Policy {
+    pub exploration: f64,
+    pub rave_bias: Option<f64>,
+    pub fpu: f64,
+}
+
+impl Ucb1Policy {
+    pub fn new(exploration: f64) -> Self {
+        Self {
+            exploration,
+            rave_bias: None,
+            fpu: f64::INFINITY,
+        }
+    }
+}
+
+impl Default for Ucb1Policy {
+    fn default() -> Self {
+        Self::new(std::f64::consts::SQRT_2)
+    }
+}
+
+impl<G: Game> SelectionPolicy<G> for Ucb1Policy {
+    fn select(&self, parent: &Node<NodeData<G>>) -> usize {
+        let parent_visits = parent.data().visits();
+
+        best_unlost_child(parent, |data| {
+            if data.visits() == 0 {
+                return self.fpu;
+            }
+
+            let score = ucb1(
+                data.reward_sum(),
+                data.visits(),
+                parent_visits,
+                self.exploration,
+            );
+
+            match self.rave_bias {
+                Some(bias) => {
+                    let beta = rave_beta(data.visits(), data.amaf_visits(), bias);
+                    score + beta * (data.amaf_mean_reward() - data.mean_reward())
+                }
+                None => score,
+            }
+        })
+    }
+}
+
+/// Selects the child maximizing `ucb1_tuned`, skipping children proven to lose
+/// unless every child is one.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Ucb1TunedPolicy;
+
+impl<G: Game> SelectionPolicy<G> for Ucb1TunedPolicy {
+    fn select(&self, parent: &Node<NodeData<G>>) -> usize {
+        let parent_visits = parent.data().visits();
+
+        best_unlost_child(parent, |data| {
+            ucb1_tuned(
+                data.reward_sum(),
+                data.reward_sq_sum(),
+                data.visits(),
+                parent_visits,
+            )
+        })
+    }
+}
+
+/// Selects the child maximizing `puct`, weighing each child's mean reward
+/// against the prior probability it was expanded with.
+/// Children proven to lose are skipped unless every child is one.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PuctPolicy {
+    pub c_puct: f64,
+}
+
+impl PuctPolicy {
+    pub fn new(c_puct: f64) -> Self {
+        Self { c_puct }
+    }
+}
+
+impl Default for PuctPolicy {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl<G: Game> SelectionPolicy<G> for PuctPolicy {
+    fn select(&self, parent: &Node<NodeData<G>>) -> usize {
+        let parent_visits = parent.data().visits();
+
+        best_unlost_child(parent, |data| {
+            puct(
+                data.mean_reward(),
+                data.prior(),
+                data.visits(),
+                parent_visits,
+                self.c_puct,
+            )
+        })
+    }
+}
+
+/// The index of the earliest child with the highest `score`,
+/// ignoring proven-losing children unless every child is one.
+fn best_unlost_child<G: Game>(
+    parent: &Node<NodeData<G>>,
+    score: impl Fn(&NodeData<G>) -> f64,
+) -> usize {
+    let children = parent.children();
+
+    let all_lost = children
+        .iter()
+        .all(|c| c.data().proof_status() == ProofStatus::ProvenLoss);
+    let candidates = children
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| all_lost || c.data().proof_status() != ProofStatus::ProvenLoss);
+
+    first_max_by(candidates, |(_, c)| score(c.data())).map_or(0, |(i, _)| i)
+}
@@ -0,0 +1,148 @@
+use super::NodeStats;
+use crate::{GameState, HashableState};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Maps game states to the node statistics shared by every node reaching them,
+/// turning the search tree into a graph wherever move orders converge.
+/// States are keyed by the `zobrist_hash` of their `GameState::canonicalize` form,
+/// so symmetric states share statistics, as would two distinct states colliding on a hash.
+pub struct TranspositionTable<S> {
+    hash: fn(&S) -> u64,
+    entries: RefCell<HashMap<u64, Rc<NodeStats>>>,
+}
+
+impl<S: HashableState> TranspositionTable<S> {
+    pub fn new() -> Self {
+        Self::with_hash(S::zobrist_hash)
+    }
+}
+
+impl<S> TranspositionTable<S> {
+    pub(crate) fn with_hash(hash: fn(&S) -> u64) -> Self {
+        Self {
+            hash,
+            entries: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn hash_fn(&self) -> fn(&S) -> u64 {
+        self.hash
+    }
+
+    /// The number of distinct states in the table.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+}
+
+impl<S: GameState> TranspositionTable<S> {
+    /// The statistics for `state`, starting from none if it has not been seen.
+    pub(crate) fn stats_for(&self, state: &S) -> Rc<NodeStats> {
+        let key = (self.hash)(&state.canonicalize());
+
+        Rc::clone(self.entries.borrow_mut().entry(key).or_default())
+    }
+}
+
+impl<S: HashableState> Default for TranspositionTable<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{DrawOutcome, SimpleGameAction, SimpleGameState};
+    use crate::{GameAction, PlayerColor, PlayerId};
+
+    /// A token on a number line, mirror-symmetric about zero.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct LineState(i32);
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Step(i32);
+
+    impl GameAction for Step {}
+
+    impl GameState for LineState {
+        type Action = Step;
+        type Outcome = DrawOutcome;
+
+        fn make_next(&mut self, action: Step) {
+            self.0 += action.0;
+        }
+
+        fn legal_actions(&self) -> Vec<Step> {
+            vec![Step(-1), Step(1)]
+        }
+
+        fn current_player_turn(&self) -> PlayerId {
+            PlayerColor::Black.into()
+        }
+
+        fn outcome(&self) -> Option<DrawOutcome> {
+            None
+        }
+
+        fn canonicalize(&self) -> Self {
+            LineState(self.0.abs())
+        }
+    }
+
+    impl HashableState for LineState {
+        fn zobrist_hash(&self) -> u64 {
+            self.0 as u64
+        }
+    }
+
+    #[test]
+    fn equal_states_share_statistics() {
+        let table = TranspositionTable::new();
+        let start = SimpleGameState::new();
+
+        let first = table.stats_for(&start.next(SimpleGameAction::new(2)));
+        let second = table.stats_for(&start.next(SimpleGameAction::new(2)));
+        let other = table.stats_for(&start.next(SimpleGameAction::new(3)));
+
+        assert!(Rc::ptr_eq(&first, &second));
+        assert!(!Rc::ptr_eq(&first, &other));
+        assert_eq!(2, table.len());
+    }
+
+    #[test]
+    fn symmetric_states_share_one_entry() {
+        let table = TranspositionTable::new();
+        let start = LineState(0);
+
+        let left = table.stats_for(&start.next(Step(-1)));
+        let right = table.stats_for(&start.next(Step(1)));
+
+        assert!(Rc::ptr_eq(&left, &right));
+        assert_eq!(1, table.len());
+    }
+
+    #[test]
+    fn clear_forgets_states() {
+        let table = TranspositionTable::new();
+
+        let state = SimpleGameState::new();
+
+        let before = table.stats_for(&state);
+        table.clear();
+        let after = table.stats_for(&state);
+
+        assert_eq!(1, table.len());
+        assert!(!Rc::ptr_eq(&before, &after));
+    }
+}
@@ -0,0 +1,218 @@
+use super::{first_max_by, ucb1};
+use crate::{Game, PlayerId};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use tree::arc_node::ArcNode;
+
+/// Thread-safe node statistics for tree-parallel search over an `ArcNode` tree,
+/// where several threads select, expand and backpropagate through the same nodes.
+/// Rewards are from the perspective of the player who took
+/// the action leading into the node.
+pub struct SyncNodeData<G: Game> {
+    action: Option<G::GameAction>,
+    player: Option<PlayerId>,
+    visits: AtomicU32,
+    virtual_losses: AtomicU32,
+    // An f64 stored as its bits, since there is no atomic float.
+    reward_sum: AtomicU64,
+}
+
+impl<G: Game> SyncNodeData<G> {
+    pub fn root() -> Self {
+        Self::with_action(None, None)
+    }
+
+    pub fn new(action: G::GameAction, player: PlayerId) -> Self {
+        Self::with_action(Some(action), Some(player))
+    }
+
+    fn with_action(action: Option<G::GameAction>, player: Option<PlayerId>) -> Self {
+        Self {
+            action,
+            player,
+            visits: AtomicU32::new(0),
+            virtual_losses: AtomicU32::new(0),
+            reward_sum: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    pub fn action(&self) -> Option<G::GameAction> {
+        self.action
+    }
+
+    pub fn player(&self) -> Option<PlayerId> {
+        self.player
+    }
+
+    /// Completed visits, not counting any virtual losses in flight.
+    pub fn visits(&self) -> u32 {
+        self.visits.load(Ordering::Acquire)
+    }
+
+    pub fn virtual_losses(&self) -> u32 {
+        self.virtual_losses.load(Ordering::Acquire)
+    }
+
+    pub fn reward_sum(&self) -> f64 {
+        f64::from_bits(self.reward_sum.load(Ordering::Acquire))
+    }
+
+    /// Visits as selection sees them: each virtual loss counts as a visit
+    /// with a reward of 0.
+    pub fn effective_visits(&self) -> u32 {
+        self.visits() + self.virtual_losses()
+    }
+
+    /// Makes the node look `loss` visits worse to other threads' selection,
+    /// until the same amount is reverted.
+    pub fn apply_virtual_loss(&self, loss: u32) {
+        self.virtual_losses.fetch_add(loss, Ordering::AcqRel);
+    }
+
+    /// Removes `loss` previously added by `apply_virtual_loss`.
+    pub fn revert_virtual_loss(&self, loss: u32) {
+        self.virtual_losses.fetch_sub(loss, Ordering::AcqRel);
+    }
+
+    pub fn record(&self, reward: f64) {
+        let mut current = self.reward_sum.load(Ordering::Acquire);
+        loop {
+            let updated = (f64::from_bits(current) + reward).to_bits();
+            match self.reward_sum.compare_exchange_weak(
+                current,
+                updated,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+
+        self.visits.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// The child maximizing UCB1 over its effective visits, or `None` if `node` has no children.
+/// The chosen child then has `loss` virtual losses applied, steering other threads
+/// selecting from `node` elsewhere until `backpropagate` reverts them.
+pub fn select_with_virtual_loss<G: Game>(
+    node: &ArcNode<SyncNodeData<G>>,
+    c: f64,
+    loss: u32,
+) -> Option<ArcNode<SyncNodeData<G>>> {
+    let parent_visits = node.data().effective_visits();
+    let children = node.children();
+
+    let selected = first_max_by(children.iter(), |child| {
+        let data = child.data();
+        ucb1(data.reward_sum(), data.effective_visits(), parent_visits, c)
+    })
+    .cloned()?;
+
+    selected.data().apply_virtual_loss(loss);
+
+    Some(selected)
+}
+
+/// Records `reward_for` each node's player along `path`,
+/// reverting the `loss` applied to every node chosen by `select_with_virtual_loss`.
+/// The first node of `path` is taken to be where selection started,
+/// so it has no virtual loss to revert.
+pub fn backpropagate<G: Game>(
+    path: &[ArcNode<SyncNodeData<G>>],
+    loss: u32,
+    reward_for: impl Fn(PlayerId) -> f64,
+) {
+    for (i, node) in path.iter().enumerate() {
+        let data = node.data();
+
+        data.record(data.player().map_or(0.0, &reward_for));
+        if i > 0 {
+            data.revert_virtual_loss(loss);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{SimpleGame, SimpleGameAction};
+    use crate::PlayerColor;
+
+    fn root_with_two_children() -> ArcNode<SyncNodeData<SimpleGame>> {
+        let mut root = ArcNode::new(SyncNodeData::root());
+        root.add_all_children(vec![
+            SyncNodeData::new(SimpleGameAction::new(2), PlayerColor::Black.into()),
+            SyncNodeData::new(SimpleGameAction::new(3), PlayerColor::Black.into()),
+        ]);
+
+        // Both children have been visited equally, with the same results.
+        for child in root.children().iter() {
+            child.data().record(1.0);
+            child.data().record(0.0);
+            root.data().record(0.0);
+            root.data().record(0.0);
+        }
+
+        root
+    }
+
+    fn bump(node: &ArcNode<SyncNodeData<SimpleGame>>) -> usize {
+        node.data().action().unwrap().bump
+    }
+
+    #[test]
+    fn without_virtual_loss_selections_agree() {
+        let root = root_with_two_children();
+
+        let first = select_with_virtual_loss(&root, 1.0, 0).unwrap();
+        let second = select_with_virtual_loss(&root, 1.0, 0).unwrap();
+
+        assert_eq!(bump(&first), bump(&second));
+    }
+
+    #[test]
+    fn virtual_loss_diverges_selections() {
+        let root = root_with_two_children();
+
+        let first = select_with_virtual_loss(&root, 1.0, 3).unwrap();
+        let second = select_with_virtual_loss(&root, 1.0, 3).unwrap();
+
+        assert_eq!(2, bump(&first));
+        assert_eq!(3, bump(&second));
+        assert_eq!(3, first.data().virtual_losses());
+        assert_eq!(5, first.data().effective_visits());
+    }
+
+    #[test]
+    fn backpropagate_reverts_virtual_loss() {
+        let root = root_with_two_children();
+
+        let child = select_with_virtual_loss(&root, 1.0, 3).unwrap();
+        backpropagate(&[root.clone(), child.clone()], 3, |_| 1.0);
+
+        assert_eq!(0, child.data().virtual_losses());
+        assert_eq!(3, child.data().visits());
+        assert_eq!(2.0, child.data().reward_sum());
+        assert_eq!(5, root.data().visits());
+        assert_eq!(0.0, root.data().reward_sum());
+    }
+
+    #[test]
+    fn concurrent_records_are_not_lost() {
+        let root = ArcNode::new(SyncNodeData::<SimpleGame>::root());
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1_000 {
+                        root.data().record(0.5);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(4_000, root.data().visits());
+        assert_eq!(2_000.0, root.data().reward_sum());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{Game, GameAgent};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+/// A baseline agent that plays a uniformly random legal action.
+pub struct RandomAgent<G: Game> {
+    rng: RefCell<StdRng>,
+    g: PhantomData<G>,
+}
+
+impl<G: Game> RandomAgent<G> {
+    /// An agent whose choices are reproducible for a given `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+            g: PhantomData,
+        }
+    }
+}
+
+impl<G: Game> Default for RandomAgent<G> {
+    /// An agent seeded from system entropy.
+    fn default() -> Self {
+        Self {
+            rng: RefCell::new(StdRng::from_entropy()),
+            g: PhantomData,
+        }
+    }
+}
+
+impl<G: Game> GameAgent<G> for RandomAgent<G> {
+    /// Panics if `actions` is empty. `GameRunner` only asks for a move
+    /// in non-terminal states, so an empty slice means the game
+    /// itself is broken rather than that the agent should recover.
+    fn pick_action(&self, _: &G::GameState, actions: &[G::GameAction]) -> G::GameAction {
+        *actions
+            .choose(&mut *self.rng.borrow_mut())
+            .expect("RandomAgent was asked to pick from no actions.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{SimpleGame, SimpleGameAction, SimpleGameState};
+    use crate::GameState;
+
+    fn picks(agent: &RandomAgent<SimpleGame>, count: usize) -> Vec<usize> {
+        let state = SimpleGameState::new();
+        let actions = state.legal_actions();
+
+        (0..count)
+            .map(|_| agent.pick_action(&state, &actions).bump)
+            .collect()
+    }
+
+    #[test]
+    fn seeded_agent_picks_deterministic_sequence() {
+        let agent = RandomAgent::with_seed(7);
+
+        assert_eq!(vec![3, 2, 2, 2, 2, 3, 4, 2, 4, 3], picks(&agent, 10));
+        assert_eq!(
+            picks(&RandomAgent::with_seed(7), 10),
+            picks(&RandomAgent::with_seed(7), 10)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_actions_panics() {
+        let agent = RandomAgent::<SimpleGame>::with_seed(0);
+        let _: SimpleGameAction = agent.pick_action(&SimpleGameState::new(), &[]);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::{Game, GameAgent, GameOutcome, GameResult, GameRunner, PlayerId};
+use std::collections::HashMap;
+
+type AgentFactory<G> = Box<dyn Fn() -> Box<dyn GameAgent<G>>>;
+
+/// Plays a round-robin between two-player agents.
+/// Every entrant plays every other entrant from both seats,
+/// with a fresh agent built from its factory for each game.
+pub struct Tournament<G: Game> {
+    entrants: Vec<(String, AgentFactory<G>)>,
+    games_per_pairing: usize,
+    start_state: G::GameState,
+}
+
+/// Identifies a tournament entrant by the order it was added, starting from 0.
+#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+pub struct AgentId(pub usize);
+
+/// One entrant's tally of games against a single opponent, or overall.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct MatchRecord {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+impl MatchRecord {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    /// One point per win and half a point per draw.
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    fn add(&mut self, other: MatchRecord) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.draws += other.draws;
+    }
+}
+
+/// An entrant's overall row in the tournament standings.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Standing {
+    pub id: AgentId,
+    pub name: String,
+    pub record: MatchRecord,
+}
+
+/// The results of a finished tournament.
+pub struct TournamentResults {
+    names: Vec<String>,
+    records: Vec<Vec<MatchRecord>>,
+}
+
+impl TournamentResults {
+    /// The entrants' names, in the order they were added.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// How entrant `player` fared against entrant `opponent`, from both seats.
+    pub fn record(&self, player: AgentId, opponent: AgentId) -> MatchRecord {
+        self.records[player.0][opponent.0]
+    }
+
+    /// Every entrant's overall record, from most points to fewest.
+    /// Entrants level on points stay in the order they were added.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .names
+            .iter()
+            .zip(&self.records)
+            .enumerate()
+            .map(|(id, (name, row))| {
+                let mut record = MatchRecord::default();
+                row.iter().for_each(|&r| record.add(r));
+
+                Standing {
+                    id: AgentId(id),
+                    name: name.clone(),
+                    record,
+                }
+            })
+            .collect();
+
+        standings.sort_by(|a, b| b.record.points().total_cmp(&a.record.points()));
+
+        standings
+    }
+}
+
+const ELO_MEAN: f64 = 1500.0;
+const ELO_MAX_ITERATIONS: usize = 10_000;
+const ELO_TOLERANCE: f64 = 1e-6;
+
+/// Fits Elo ratings to a tournament's results, with draws as half a win
+/// for each side and the ratings shifted so that their mean is 1500.
+/// An entrant that won or lost every game has no finite best-fit rating,
+/// so fitting stops after a fixed number of iterations if it has not converged.
+pub fn elo_ratings(results: &TournamentResults) -> HashMap<AgentId, f64> {
+    let count = results.names.len();
+    let mut ratings = vec![ELO_MEAN; count];
+
+    for _ in 0..ELO_MAX_ITERATIONS {
+        let mut largest_change: f64 = 0.0;
+
+        for player in 0..count {
+            let mut expected = 0.0;
+            let mut scored = 0.0;
+            let mut games = 0;
+
+            for (opponent, record) in results.records[player].iter().enumerate() {
+                let win_chance =
+                    1.0 / (1.0 + 10f64.powf((ratings[opponent] - ratings[player]) / 400.0));
+
+                expected += win_chance * record.games() as f64;
+                scored += record.points();
+                games += record.games();
+            }
+
+            if games > 0 {
+                let change = 32.0 * (scored - expected) / games as f64;
+                ratings[player] += change;
+                largest_change = largest_change.max(change.abs());
+            }
+        }
+
+        let shift = ELO_MEAN - ratings.iter().sum::<f64>() / count as f64;
+        ratings.iter_mut().for_each(|r| *r += shift);
+
+        if largest_change < ELO_TOLERANCE {
+            break;
+        }
+    }
+
+    ratings
+        .into_iter()
+        .enumerate()
+        .map(|(id, rating)| (AgentId(id), rating))
+        .collect()
+}
+
+impl<G: Game> Tournament<G> {
+    /// A tournament where every game begins from `start_state`.
+    pub fn new(start_state: G::GameState) -> Self {
+        Self {
+            entrants: Vec::new(),
+            games_per_pairing: 1,
+            start_state,
+        }
+    }
+
+    pub fn with_entrant(
+        mut self,
+        name: impl Into<String>,
+        factory: impl Fn() -> Box<dyn GameAgent<G>> + 'static,
+    ) -> Self {
+        self.entrants.push((name.into(), Box::new(factory)));
+        self
+    }
+
+    /// How many games each entrant plays against each opponent from each seat.
+    /// Defaults to 1.
+    pub fn with_games_per_pairing(mut self, games: usize) -> Self {
+        self.games_per_pairing = games;
+        self
+    }
+
+    /// Plays every game of the tournament.
+    /// Panics if a game is won by a player other than `PlayerId(0)` or `PlayerId(1)`.
+    pub fn play(&self) -> TournamentResults {
+        let count = self.entrants.len();
+        let mut records = vec![vec![MatchRecord::default(); count]; count];
+
+        let pairings = (0..count).flat_map(|first| {
+            (0..count)
+                .filter(move |&s| s != first)
+                .map(move |s| (first, s))
+        });
+
+        for (first, second) in pairings {
+            for _ in 0..self.games_per_pairing {
+                match self.play_game(first, second) {
+                    Some(PlayerId(0)) => {
+                        records[first][second].wins += 1;
+                        records[second][first].losses += 1;
+                    }
+                    Some(PlayerId(1)) => {
+                        records[first][second].losses += 1;
+                        records[second][first].wins += 1;
+                    }
+                    Some(PlayerId(other)) => {
+                        panic!("Tournament games are two-player, but player {} won.", other)
+                    }
+                    None => {
+                        records[first][second].draws += 1;
+                        records[second][first].draws += 1;
+                    }
+                }
+            }
+        }
+
+        TournamentResults {
+            names: self.entrants.iter().map(|(name, _)| name.clone()).collect(),
+            records,
+        }
+    }
+
+    /// Plays one game with entrant `first` in the first seat,
+    /// returning the winning seat, if any.
+    fn play_game(&self, first: usize, second: usize) -> Option<PlayerId> {
+        let agents = vec![(self.entrants[first].1)(), (self.entrants[second].1)()];
+        let record = GameRunner::<G>::new(agents, self.start_state.clone()).play();
+
+        match record.result {
+            GameResult::Finished(outcome) => outcome.winner(),
+            GameResult::TimedOut(PlayerId(loser)) => Some(PlayerId(1 - loser)),
+            GameResult::Drawn(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{DrawAction, DrawGame, DrawState};
+
+    struct Always(DrawAction);
+
+    impl GameAgent<DrawGame> for Always {
+        fn pick_action(&self, _: &DrawState, _: &[DrawAction]) -> DrawAction {
+            self.0
+        }
+    }
+
+    /// Only the first seat moves in a `DrawGame`,
+    /// so each game goes the way its first player chooses.
+    fn tournament() -> Tournament<DrawGame> {
+        Tournament::new(DrawState::default())
+            .with_entrant("drawer", || Box::new(Always(DrawAction::Draw)))
+            .with_entrant("winner", || Box::new(Always(DrawAction::Win)))
+            .with_entrant("other winner", || Box::new(Always(DrawAction::Win)))
+            .with_games_per_pairing(3)
+    }
+
+    #[test]
+    fn records_follow_known_matchups() {
+        let results = tournament().play();
+
+        let drawn_then_lost = MatchRecord {
+            wins: 0,
+            losses: 3,
+            draws: 3,
+        };
+        let won_then_drawn = MatchRecord {
+            wins: 3,
+            losses: 0,
+            draws: 3,
+        };
+        let split = MatchRecord {
+            wins: 3,
+            losses: 3,
+            draws: 0,
+        };
+
+        let [drawer, winner, other_winner] = [AgentId(0), AgentId(1), AgentId(2)];
+
+        assert_eq!(drawn_then_lost, results.record(drawer, winner));
+        assert_eq!(drawn_then_lost, results.record(drawer, other_winner));
+        assert_eq!(won_then_drawn, results.record(winner, drawer));
+        assert_eq!(split, results.record(winner, other_winner));
+        assert_eq!(split, results.record(other_winner, winner));
+        assert_eq!(MatchRecord::default(), results.record(winner, winner));
+    }
+
+    #[test]
+    fn standings_rank_by_points() {
+        let standings = tournament().play().standings();
+
+        let names: Vec<&str> = standings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(vec!["winner", "other winner", "drawer"], names);
+        assert_eq!(AgentId(1), standings[0].id);
+
+        assert_eq!(6, standings[0].record.wins);
+        assert_eq!(7.5, standings[0].record.points());
+        assert_eq!(12, standings[2].record.games());
+        assert_eq!(3.0, standings[2].record.points());
+    }
+
+    fn results_from(wins: Vec<Vec<usize>>) -> TournamentResults {
+        let records = wins
+            .iter()
+            .enumerate()
+            .map(|(player, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(opponent, &won)| MatchRecord {
+                        wins: won,
+                        losses: wins[opponent][player],
+                        draws: 0,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        TournamentResults {
+            names: (0..wins.len()).map(|i| i.to_string()).collect(),
+            records,
+        }
+    }
+
+    #[test]
+    fn elo_ranks_the_dominant_agent_highest() {
+        // Agent 0 beats everyone; agents 1 and 2 trade wins, 1 slightly better.
+        let results = results_from(vec![vec![0, 5, 5], vec![0, 0, 3], vec![0, 2, 0]]);
+
+        let ratings = elo_ratings(&results);
+
+        assert!(ratings[&AgentId(0)] > ratings[&AgentId(1)]);
+        assert!(ratings[&AgentId(1)] > ratings[&AgentId(2)]);
+
+        let mean = ratings.values().sum::<f64>() / ratings.len() as f64;
+        assert!((mean - 1500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn elo_rates_even_results_equally() {
+        let results = tournament().play();
+
+        let ratings = elo_ratings(&results);
+
+        assert!((ratings[&AgentId(1)] - ratings[&AgentId(2)]).abs() < 1e-6);
+        assert!(ratings[&AgentId(0)] < ratings[&AgentId(1)]);
+    }
+}
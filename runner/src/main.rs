@@ -1,6 +1,3 @@
-use libgame::Game;
-use tree::node::Node;
-
 fn main() {
     println!("Hello, world!");
 }
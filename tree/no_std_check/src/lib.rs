@@ -0,0 +1,33 @@
+//! Compiles only if the `tree` core builds under `no_std` with `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use tree::arena::Arena;
+use tree::cursor::Cursor;
+use tree::node::Node;
+
+/// Exercises the `no_std` API, returning the data of both trees in pre-order.
+pub fn build_trees() -> (Vec<i32>, Vec<i32>) {
+    let mut root = Node::new(0);
+    root.add_all_children([1, 2]);
+    let mut cursor = Cursor::new(root.clone());
+    cursor.move_to_child(0);
+    cursor.add_child(3);
+
+    let mut arena = Arena::new();
+    let arena_root = arena.new_root(0);
+    let first = arena.add_child(arena_root, 1);
+    arena.add_child(arena_root, 2);
+    arena.add_child(first, 3);
+
+    (
+        root.iter_preorder().map(|n| *n.data()).collect(),
+        arena
+            .iter_preorder(arena_root)
+            .map(|id| *arena.data(id))
+            .collect(),
+    )
+}
@@ -0,0 +1,207 @@
+use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use std::hint;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+
+/// A thread-safe counterpart to `Node`,
+/// which can be shared and extended across threads.
+#[derive(Default, Debug)]
+pub struct ArcNode<T>(Arc<ArcNodeInternal<T>>);
+
+#[derive(Default, Debug)]
+struct ArcNodeInternal<T> {
+    data: T,
+    parent: Weak<Self>,
+    children: AtomicRefCell<Vec<ArcNode<T>>>,
+    /// How many threads are waiting to append to `children`,
+    /// which new readers wait out so that appending threads are not starved.
+    pending_appends: AtomicUsize,
+}
+
+impl<T> ArcNode<T> {
+    pub fn new(data: T) -> Self {
+        let internal = ArcNodeInternal {
+            data,
+            parent: Weak::new(),
+            children: AtomicRefCell::new(Vec::new()),
+            pending_appends: AtomicUsize::new(0),
+        };
+
+        Self(Arc::new(internal))
+    }
+
+    /// Appends the children, waiting out any other thread
+    /// that is appending to this node at the same time.
+    pub fn add_all_children(&mut self, children_data: impl IntoIterator<Item = T>) {
+        let this_node = self.get_arc();
+        let mut children = children_data
+            .into_iter()
+            .map(|c| ArcNodeInternal {
+                data: c,
+                parent: Arc::downgrade(this_node),
+                children: AtomicRefCell::new(Vec::new()),
+                pending_appends: AtomicUsize::new(0),
+            })
+            .map(|i| Self(Arc::new(i)))
+            .collect();
+
+        this_node.pending_appends.fetch_add(1, Ordering::AcqRel);
+
+        loop {
+            if let Ok(mut existing) = this_node.children.try_borrow_mut() {
+                existing.append(&mut children);
+                this_node.pending_appends.fetch_sub(1, Ordering::AcqRel);
+                return;
+            }
+
+            hint::spin_loop();
+        }
+    }
+
+    pub fn data(&self) -> &T {
+        &self.get_arc().data
+    }
+
+    /// Waits out any other thread that is appending, or waiting to append, to this node's children.
+    /// Holding the result makes appending threads wait in turn,
+    /// so appending to this node from the same thread would never return.
+    pub fn children(&self) -> AtomicRef<'_, Vec<Self>> {
+        let internal = self.get_arc();
+
+        loop {
+            if internal.pending_appends.load(Ordering::Acquire) == 0 {
+                if let Ok(children) = internal.children.try_borrow() {
+                    return children;
+                }
+            }
+
+            hint::spin_loop();
+        }
+    }
+
+    /// Waits out any other thread that is reading or appending to this node's children.
+    pub fn children_mut(&mut self) -> AtomicRefMut<'_, Vec<Self>> {
+        loop {
+            if let Ok(children) = self.get_arc().children.try_borrow_mut() {
+                return children;
+            }
+
+            hint::spin_loop();
+        }
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        self.get_arc().parent.upgrade().map(Self)
+    }
+
+    fn get_arc(&self) -> &Arc<ArcNodeInternal<T>> {
+        &self.0
+    }
+}
+
+impl<T> std::clone::Clone for ArcNode<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[derive(Debug, PartialEq)]
+    struct NoCopy(i32);
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn arc_node_is_send_and_sync() {
+        assert_send_sync::<ArcNode<NoCopy>>();
+    }
+
+    #[test]
+    fn root_with_children() {
+        let mut root = ArcNode::new(NoCopy(42));
+
+        root.add_all_children(vec![NoCopy(1), NoCopy(2)]);
+
+        assert_eq!(NoCopy(42), *root.data());
+        assert_eq!(2, root.children().len());
+        assert_eq!(NoCopy(2), *root.children()[1].data());
+        assert_eq!(NoCopy(42), *root.children()[0].parent().unwrap().data());
+        assert!(root.parent().is_none());
+    }
+
+    #[test]
+    fn children_mut_allows_nested_expansion() {
+        let mut root = ArcNode::new(NoCopy(42));
+
+        root.add_all_children(vec![NoCopy(1)]);
+        root.children_mut()[0].add_all_children(vec![NoCopy(2), NoCopy(3)]);
+
+        assert_eq!(2, root.children()[0].children().len());
+    }
+
+    #[test]
+    fn threads_read_and_append_concurrently() {
+        let root = ArcNode::new(NoCopy(42));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let mut node = root.clone();
+                thread::spawn(move || {
+                    assert_eq!(NoCopy(42), *node.data());
+                    node.add_all_children(vec![NoCopy(i * 10), NoCopy(i * 10 + 1)]);
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let children = root.children();
+        assert_eq!(16, children.len());
+
+        let mut values: Vec<i32> = children.iter().map(|c| c.data().0).collect();
+        values.sort_unstable();
+        let expected: Vec<i32> = (0..8).flat_map(|i| vec![i * 10, i * 10 + 1]).collect();
+        assert_eq!(expected, values);
+
+        assert!(children
+            .iter()
+            .all(|c| *c.parent().unwrap().data() == NoCopy(42)));
+    }
+
+    #[test]
+    fn threads_iterate_children_while_others_append() {
+        let root = ArcNode::new(NoCopy(0));
+
+        thread::scope(|s| {
+            for i in 0..4 {
+                let mut node = root.clone();
+                s.spawn(move || {
+                    for j in 0..250 {
+                        node.add_all_children(vec![NoCopy(i * 1000 + j)]);
+                    }
+                });
+            }
+
+            for _ in 0..4 {
+                let node = root.clone();
+                s.spawn(move || {
+                    let mut seen = 0;
+                    while seen < 1000 {
+                        let children = node.children();
+                        assert!(children.len() >= seen);
+                        assert!(children.iter().all(|c| c.data().0 >= 0));
+                        seen = children.len();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(1000, root.children().len());
+    }
+}
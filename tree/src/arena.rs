@@ -0,0 +1,219 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A handle to a node in an `Arena`.
+/// It is only meaningful for the arena that handed it out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A tree, or several, stored in a single `Vec`, with nodes referring
+/// to one another by `NodeId` rather than by pointer.
+/// This trades `Node`'s shared handles and per-node allocations for
+/// better cache locality, cheaper construction and one drop for the whole tree.
+/// Nodes cannot be removed.
+#[derive(Debug)]
+pub struct Arena<T> {
+    nodes: Vec<ArenaNode<T>>,
+}
+
+#[derive(Debug)]
+struct ArenaNode<T> {
+    data: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    /// Adds a node without a parent.
+    pub fn new_root(&mut self, data: T) -> NodeId {
+        self.push(data, None)
+    }
+
+    /// Appends a child to `parent`.
+    /// Panics if `parent` is not a node of this arena.
+    pub fn add_child(&mut self, parent: NodeId, data: T) -> NodeId {
+        assert!(self.contains(parent), "No node {:?} in this arena.", parent);
+
+        let child = self.push(data, Some(parent));
+        self.nodes[parent.0].children.push(child);
+
+        child
+    }
+
+    /// Panics if `id` is not a node of this arena.
+    pub fn data(&self, id: NodeId) -> &T {
+        &self.node(id).data
+    }
+
+    /// Panics if `id` is not a node of this arena.
+    pub fn data_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.nodes[id.0].data
+    }
+
+    /// Panics if `id` is not a node of this arena.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.node(id).children
+    }
+
+    /// Panics if `id` is not a node of this arena.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).parent
+    }
+
+    /// Whether `id` refers to a node of this arena.
+    pub fn contains(&self, id: NodeId) -> bool {
+        id.0 < self.nodes.len()
+    }
+
+    /// The data of `id`, or `None` if it is not a node of this arena.
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.nodes.get(id.0).map(|n| &n.data)
+    }
+
+    /// The number of nodes across every tree in the arena.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Walks the subtree rooted at `id` depth-first,
+    /// yielding each node before its children, and children left-to-right.
+    pub fn iter_preorder(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = vec![id];
+
+        core::iter::from_fn(move || {
+            let id = stack.pop()?;
+            stack.extend(self.children(id).iter().rev());
+
+            Some(id)
+        })
+    }
+
+    fn push(&mut self, data: T, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(ArenaNode {
+            data,
+            parent,
+            children: Vec::new(),
+        });
+
+        id
+    }
+
+    fn node(&self, id: NodeId) -> &ArenaNode<T> {
+        self.nodes
+            .get(id.0)
+            .unwrap_or_else(|| panic!("No node {:?} in this arena.", id))
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn root_with_children() {
+        let mut arena = Arena::new();
+
+        let root = arena.new_root(42);
+        let first = arena.add_child(root, 1);
+        let second = arena.add_child(root, 2);
+
+        assert_eq!(42, *arena.data(root));
+        assert_eq!(&[first, second], arena.children(root));
+        assert_eq!(Some(root), arena.parent(second));
+        assert_eq!(None, arena.parent(root));
+        assert_eq!(3, arena.len());
+    }
+
+    #[test]
+    fn data_mut_updates_node() {
+        let mut arena = Arena::new();
+        let root = arena.new_root(1);
+
+        *arena.data_mut(root) += 1;
+
+        assert_eq!(2, *arena.data(root));
+    }
+
+    #[test]
+    fn ids_from_another_arena_are_invalid() {
+        let mut small = Arena::new();
+        let mut large = Arena::new();
+        let root = large.new_root(0);
+        let child = large.add_child(root, 1);
+        small.new_root(0);
+
+        assert!(small.contains(root));
+        assert!(!small.contains(child));
+        assert_eq!(None, small.get(child));
+        assert_eq!(Some(&1), large.get(child));
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_child_to_invalid_parent_panics() {
+        let mut other = Arena::new();
+        let root = other.new_root(0);
+        let child = other.add_child(root, 1);
+
+        let mut arena = Arena::new();
+        arena.new_root(0);
+        arena.add_child(child, 2);
+    }
+
+    #[test]
+    fn large_tree_matches_node_version() {
+        let mut arena = Arena::new();
+        let arena_root = arena.new_root(0);
+        let node_root = Node::new(0);
+
+        // Each node gets up to three children, breadth-first, until there are 10,000 nodes.
+        let mut frontier = VecDeque::from(vec![(arena_root, node_root.clone())]);
+        let mut next_value = 1;
+        while next_value < 10_000 {
+            let (id, mut node) = frontier.pop_front().unwrap();
+            let values: Vec<i32> = (next_value..(next_value + 3).min(10_000)).collect();
+            next_value += values.len() as i32;
+
+            let ids: Vec<NodeId> = values.iter().map(|&v| arena.add_child(id, v)).collect();
+            node.add_all_children(values);
+
+            frontier.extend(ids.into_iter().zip(node.children().iter().cloned()));
+        }
+
+        let from_arena: Vec<i32> = arena
+            .iter_preorder(arena_root)
+            .map(|id| *arena.data(id))
+            .collect();
+        let from_nodes: Vec<i32> = node_root.iter_preorder().map(|n| *n.data()).collect();
+
+        assert_eq!(10_000, arena.len());
+        assert_eq!(from_nodes, from_arena);
+
+        let arena_parents: Vec<Option<i32>> = arena
+            .iter_preorder(arena_root)
+            .map(|id| arena.parent(id).map(|p| *arena.data(p)))
+            .collect();
+        let node_parents: Vec<Option<i32>> = node_root
+            .iter_preorder()
+            .map(|n| n.parent().map(|p| *p.data()))
+            .collect();
+        assert_eq!(node_parents, arena_parents);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::node::Node;
+
+/// A stateful position within a `Node` tree,
+/// which can be moved up and down while mutating the tree along the way.
+pub struct Cursor<T> {
+    current: Node<T>,
+}
+
+impl<T> Cursor<T> {
+    pub fn new(start: Node<T>) -> Self {
+        Self { current: start }
+    }
+
+    pub fn current(&self) -> &Node<T> {
+        &self.current
+    }
+
+    pub fn data(&self) -> &T {
+        self.current.data()
+    }
+
+    /// Moves to the child at index `i`.
+    /// Returns false, without moving, if there is no such child.
+    pub fn move_to_child(&mut self, i: usize) -> bool {
+        match self.current.nth_child(i) {
+            Some(child) => {
+                self.current = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to the parent.
+    /// Returns false, without moving, if already at the root.
+    pub fn move_to_parent(&mut self) -> bool {
+        match self.current.parent() {
+            Some(parent) => {
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_to_root(&mut self) {
+        while self.move_to_parent() {}
+    }
+
+    /// Appends a child to the current node, without moving.
+    pub fn add_child(&mut self, data: T) {
+        self.current.add_child(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn down_then_up_with_mutation_at_leaf() {
+        let mut root = Node::new(0);
+        root.add_all_children(vec![1, 2]);
+
+        let mut cursor = Cursor::new(root.clone());
+
+        assert!(cursor.move_to_child(1));
+        assert_eq!(2, *cursor.data());
+        assert!(cursor.current().is_leaf());
+
+        cursor.add_child(3);
+        assert!(cursor.move_to_child(0));
+        assert_eq!(3, *cursor.data());
+
+        assert!(cursor.move_to_parent());
+        assert_eq!(2, *cursor.data());
+
+        cursor.move_to_root();
+        assert_eq!(0, *cursor.data());
+        assert!(cursor.current().ptr_eq(&root));
+
+        assert_eq!(1, root.children()[1].child_count());
+        assert_eq!(3, *root.children()[1].children()[0].data());
+    }
+
+    #[test]
+    fn failed_moves_stay_put() {
+        let root = Node::new(0);
+        let mut cursor = Cursor::new(root);
+
+        assert!(!cursor.move_to_parent());
+        assert!(!cursor.move_to_child(0));
+        assert_eq!(0, *cursor.data());
+    }
+}
@@ -1,5 +1,21 @@
+//! Without the default `std` feature, this crate is `no_std` and needs only `alloc`:
+//! `node`, `cursor` and `arena` remain, while the thread-safe `arc_node`
+//! and `write_once_lock`, and serde support, need `std`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod arc_node;
+pub mod arena;
+pub mod cursor;
 mod merger;
 pub mod node;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "std")]
+pub mod write_once_lock;
 
 #[cfg(test)]
 mod tests {
@@ -8,6 +24,6 @@ mod tests {
 
     #[test]
     fn copy_type_works() {
-        let x = Node::new(48);
+        let _x = Node::new(48);
     }
 }
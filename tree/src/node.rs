@@ -1,9 +1,132 @@
-use std::cell::{Ref, RefCell, RefMut};
-use std::rc::{Rc, Weak};
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::format;
+use alloc::rc::{Rc, Weak};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::{Ref, RefCell, RefMut};
+use core::fmt;
 
 #[derive(Default, Debug)]
 pub struct Node<T>(Rc<NodeInternal<T>>);
 
+/// A non-owning handle to a `Node`, which does not keep it or its subtree alive,
+/// for side tables and back-references that should not own the tree.
+#[derive(Debug)]
+pub struct WeakNode<T>(Weak<NodeInternal<T>>);
+
+impl<T> WeakNode<T> {
+    /// An owning handle to the node, or `None` if it has been dropped.
+    pub fn upgrade(&self) -> Option<Node<T>> {
+        self.0.upgrade().map(Node)
+    }
+}
+
+impl<T> Clone for WeakNode<T> {
+    fn clone(&self) -> Self {
+        Self(Weak::clone(&self.0))
+    }
+}
+
+/// Recycles the allocations of nodes that are no longer needed,
+/// so that a tree which is repeatedly pruned and regrown, as in a long search,
+/// reuses the same allocations rather than freeing and allocating new ones.
+/// Nodes go in through `recycle` or `Node::clear_children_into`,
+/// and come back out through `Node::add_child_from` and `Node::add_all_children_from`.
+/// A pooled node's old data is only dropped once the node is reused, or with the pool.
+#[derive(Debug)]
+pub struct NodePool<T> {
+    free: Vec<Rc<NodeInternal<T>>>,
+    allocated: usize,
+    reused: usize,
+}
+
+impl<T> NodePool<T> {
+    pub fn new() -> Self {
+        Self {
+            free: Vec::new(),
+            allocated: 0,
+            reused: 0,
+        }
+    }
+
+    /// Takes back every node of `subtree`, which should already be detached
+    /// from any parent, such as a node returned by `Node::remove_child`.
+    /// As when dropping a tree, a node still referenced by another `Node` handle
+    /// is left alone along with its subtree, though it no longer reports the
+    /// recycled node as its parent. A node referenced only by a `WeakNode`
+    /// is dropped as usual instead of being recycled,
+    /// so that the `WeakNode` can never upgrade to an unrelated, reused node.
+    pub fn recycle(&mut self, subtree: Node<T>) {
+        let mut pending = vec![subtree.0];
+
+        while let Some(mut rc) = pending.pop() {
+            // Each child holds a weak link to its parent; any more are from elsewhere.
+            if Rc::strong_count(&rc) != 1 || Rc::weak_count(&rc) > rc.children.borrow().len() {
+                continue;
+            }
+
+            for Node(child) in core::mem::take(&mut *rc.children.borrow_mut()) {
+                *child.parent.borrow_mut() = Weak::new();
+                pending.push(child);
+            }
+            *rc.parent.borrow_mut() = Weak::new();
+
+            if Rc::get_mut(&mut rc).is_some() {
+                self.free.push(rc);
+            }
+        }
+    }
+
+    /// The number of nodes waiting to be reused.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// The number of nodes the pool has had to allocate because none were free.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// The number of nodes handed out again after being recycled.
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    fn make(&mut self, data: T, parent: Weak<NodeInternal<T>>) -> Node<T> {
+        match self.free.pop() {
+            Some(mut rc) => {
+                let internal = Rc::get_mut(&mut rc).expect("Pooled nodes are never shared.");
+                internal.data = data;
+                *internal.parent.get_mut() = parent;
+                self.reused += 1;
+
+                Node(rc)
+            }
+            None => {
+                self.allocated += 1;
+
+                Node(Rc::new(NodeInternal {
+                    data,
+                    parent: RefCell::new(parent),
+                    children: RefCell::new(Vec::new()),
+                }))
+            }
+        }
+    }
+}
+
+impl<T> Default for NodePool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Default, Debug, Copy, Clone)]
 struct MctsData {
     rollouts: usize,
@@ -11,10 +134,46 @@ struct MctsData {
     is_saturated: bool,
 }
 
+/// Why `Node::from_parent_indices` could not build a tree.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParentIndicesError {
+    /// `data` and `parents` have different lengths.
+    LengthMismatch { data: usize, parents: usize },
+    /// The parent of the element at `index` is past the end of `data`.
+    ParentOutOfRange { index: usize },
+    /// No element has a `None` parent.
+    NoRoot,
+    /// More than one element has a `None` parent.
+    MultipleRoots,
+    /// Some elements' parents lead around in a loop rather than up to the root.
+    Cycle,
+}
+
+impl fmt::Display for ParentIndicesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { data, parents } => write!(
+                f,
+                "{} data elements were given but {} parent indices.",
+                data, parents
+            ),
+            Self::ParentOutOfRange { index } => {
+                write!(f, "The parent of element {} is out of range.", index)
+            }
+            Self::NoRoot => write!(f, "No element is the root."),
+            Self::MultipleRoots => write!(f, "More than one element is a root."),
+            Self::Cycle => write!(f, "The parent indices contain a cycle."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParentIndicesError {}
+
 #[derive(Default, Debug)]
 struct NodeInternal<T> {
     data: T,
-    parent: Weak<Self>,
+    parent: RefCell<Weak<Self>>,
     children: RefCell<Vec<Node<T>>>,
 }
 
@@ -22,20 +181,77 @@ impl<T> Node<T> {
     pub fn new(data: T) -> Self {
         let internal = NodeInternal {
             data,
-            parent: Weak::new(),
+            parent: RefCell::new(Weak::new()),
             children: RefCell::new(Vec::new()),
         };
 
         Self(Rc::new(internal))
     }
 
+    /// Builds a tree from a flat representation, where element `i` has data `data[i]`
+    /// and is a child of element `parents[i]`, or is the root if that is `None`.
+    /// Children are ordered by their index in `data`.
+    /// Exactly one element must be the root, and every other must reach it through its parents.
+    pub fn from_parent_indices(
+        data: Vec<T>,
+        parents: &[Option<usize>],
+    ) -> Result<Self, ParentIndicesError> {
+        if data.len() != parents.len() {
+            return Err(ParentIndicesError::LengthMismatch {
+                data: data.len(),
+                parents: parents.len(),
+            });
+        }
+
+        let mut children = vec![Vec::new(); data.len()];
+        let mut root = None;
+
+        for (index, parent) in parents.iter().enumerate() {
+            match *parent {
+                Some(p) if p >= data.len() => {
+                    return Err(ParentIndicesError::ParentOutOfRange { index })
+                }
+                Some(p) => children[p].push(index),
+                None if root.is_some() => return Err(ParentIndicesError::MultipleRoots),
+                None => root = Some(index),
+            }
+        }
+
+        let root_index = root.ok_or(ParentIndicesError::NoRoot)?;
+
+        // With a single root, any element it cannot reach must be caught in a cycle.
+        let mut data: Vec<Option<T>> = data.into_iter().map(Some).collect();
+        let root = Node::new(data[root_index].take().unwrap());
+        let mut stack = vec![(root.clone(), root_index)];
+        let mut reached = 1;
+
+        while let Some((mut node, index)) = stack.pop() {
+            let child_indices = &children[index];
+            node.add_all_children(child_indices.iter().map(|&c| data[c].take().unwrap()));
+            reached += child_indices.len();
+
+            stack.extend(
+                node.children()
+                    .iter()
+                    .cloned()
+                    .zip(child_indices.iter().copied()),
+            );
+        }
+
+        if reached < data.len() {
+            return Err(ParentIndicesError::Cycle);
+        }
+
+        Ok(root)
+    }
+
     pub fn add_all_children(&mut self, children_data: impl IntoIterator<Item = T>) {
         let this_node = self.get_rc();
         let mut children = children_data
             .into_iter()
             .map(|c| NodeInternal {
                 data: c,
-                parent: Rc::downgrade(this_node),
+                parent: RefCell::new(Rc::downgrade(this_node)),
                 children: RefCell::new(Vec::new()),
             })
             .map(|i| Self(Rc::new(i)))
@@ -44,28 +260,400 @@ impl<T> Node<T> {
         this_node.children.borrow_mut().append(&mut children);
     }
 
+    /// Appends one child holding `child_data`, taking its node from `pool`
+    /// if the pool has one and allocating it otherwise.
+    /// `add_all_children` never takes nodes from a pool; this method is how one is used.
+    pub fn add_child_from(&mut self, pool: &mut NodePool<T>, child_data: T) {
+        let child = pool.make(child_data, Rc::downgrade(self.get_rc()));
+
+        self.get_rc().children.borrow_mut().push(child);
+    }
+
+    /// Appends a child for each of `children_data`, in order, taking their nodes
+    /// from `pool` while it has any and allocating the rest.
+    /// Unlike `add_all_children`, which always allocates, this reuses nodes that
+    /// `clear_children_into` or `NodePool::recycle` returned to the pool.
+    pub fn add_all_children_from(
+        &mut self,
+        pool: &mut NodePool<T>,
+        children_data: impl IntoIterator<Item = T>,
+    ) {
+        let this_node = self.get_rc();
+        let mut children = children_data
+            .into_iter()
+            .map(|c| pool.make(c, Rc::downgrade(this_node)))
+            .collect();
+
+        this_node.children.borrow_mut().append(&mut children);
+    }
+
+    /// Reserves room for at least `additional` more children,
+    /// so that a node's children can all be added without reallocating.
+    pub fn reserve_children(&mut self, additional: usize) {
+        self.get_rc().children.borrow_mut().reserve(additional);
+    }
+
     pub fn data(&self) -> &T {
         &self.get_rc().data
     }
 
-    pub fn children(&self) -> Ref<Vec<Self>> {
+    pub fn children(&self) -> Ref<'_, Vec<Self>> {
         let rc = self.get_rc();
 
         rc.children.borrow()
     }
 
-    pub fn children_mut(&mut self) -> RefMut<Vec<Self>> {
+    pub fn children_mut(&mut self) -> RefMut<'_, Vec<Self>> {
         let rc = self.get_rc();
 
         rc.children.borrow_mut()
     }
 
+    pub fn child_count(&self) -> usize {
+        self.children().len()
+    }
+
+    /// A handle to the child at index `i`, or `None` if out of range.
+    /// Unlike indexing into `children()`, no borrow is held afterwards.
+    pub fn nth_child(&self, i: usize) -> Option<Node<T>> {
+        self.children().get(i).cloned()
+    }
+
+    /// Removes and returns the child at `index`, or `None` if out of bounds.
+    /// The removed node keeps its weak link to this node,
+    /// so it still reports this node as its parent until this node is dropped.
+    pub fn remove_child(&mut self, index: usize) -> Option<Node<T>> {
+        let mut children = self.children_mut();
+
+        if index < children.len() {
+            Some(children.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes every direct child whose data satisfies `predicate`,
+    /// keeping the rest in their original order.
+    /// Pruned subtrees are dropped entirely, unless held elsewhere.
+    pub fn prune_children_where<F: FnMut(&T) -> bool>(&mut self, mut predicate: F) {
+        self.children_mut().retain(|c| !predicate(c.data()));
+    }
+
+    /// Removes every child, leaving this node's data and parent untouched,
+    /// so that the node can be reused as a fresh leaf.
+    /// The subtrees are dropped without recursing, as when dropping a tree,
+    /// except for any still referenced by some other handle.
+    pub fn clear_children(&mut self) {
+        let children = core::mem::take(&mut *self.children_mut());
+
+        drop(children);
+    }
+
+    /// Like `clear_children`, but returning the removed subtrees to `pool`.
+    pub fn clear_children_into(&mut self, pool: &mut NodePool<T>) {
+        let children = core::mem::take(&mut *self.children_mut());
+
+        for child in children {
+            pool.recycle(child);
+        }
+    }
+
     pub fn parent(&self) -> Option<Self> {
-        let maybe_rc = self.get_rc().parent.upgrade();
+        let maybe_rc = self.get_rc().parent.borrow().upgrade();
 
         maybe_rc.map(|rc| Self(rc))
     }
 
+    /// The other children of this node's parent, in order.
+    /// A root has no siblings.
+    pub fn siblings(&self) -> impl Iterator<Item = Node<T>> {
+        let siblings: Vec<Self> = match self.parent() {
+            Some(parent) => parent
+                .children()
+                .iter()
+                .filter(|c| !c.ptr_eq(self))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        siblings.into_iter()
+    }
+
+    /// Walks the subtree rooted at this node depth-first,
+    /// yielding each node before its children, and children left-to-right.
+    pub fn iter_preorder(&self) -> impl Iterator<Item = Node<T>> {
+        let mut stack = vec![self.clone()];
+
+        core::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children().iter().rev().cloned());
+
+            Some(node)
+        })
+    }
+
+    /// The first node of this subtree, in pre-order, whose data satisfies `predicate`.
+    pub fn find<F: Fn(&T) -> bool>(&self, predicate: F) -> Option<Node<T>> {
+        self.iter_preorder().find(|n| predicate(n.data()))
+    }
+
+    /// Keeps only the subtree rooted at the first descendant, in pre-order,
+    /// whose data satisfies `predicate`, detaching it as a new root,
+    /// or returns `None` if no descendant matches. This node itself is never
+    /// a candidate, whether or not it matches.
+    /// The rest of the tree is dropped along with this handle,
+    /// unless some other handle still refers to it.
+    pub fn into_subtree<F: Fn(&T) -> bool>(self, predicate: F) -> Option<Node<T>> {
+        self.iter_preorder()
+            .skip(1)
+            .find(|n| predicate(n.data()))
+            .map(|n| n.detach())
+    }
+
+    /// Yields only the leaves of this subtree, in depth-first order.
+    /// A lone root is its own only leaf.
+    pub fn iter_leaves(&self) -> impl Iterator<Item = Node<T>> {
+        self.iter_preorder().filter(|n| n.is_leaf())
+    }
+
+    /// Walks the subtree rooted at this node level by level,
+    /// yielding all nodes at one depth (left-to-right) before the next.
+    pub fn iter_bfs(&self) -> impl Iterator<Item = Node<T>> {
+        let mut queue = VecDeque::new();
+        queue.push_back(self.clone());
+
+        core::iter::from_fn(move || {
+            let node = queue.pop_front()?;
+            queue.extend(node.children().iter().cloned());
+
+            Some(node)
+        })
+    }
+
+    /// Severs this node from its parent, making it a root.
+    /// The parent no longer lists it as a child,
+    /// but its own subtree is left intact.
+    pub fn detach(&self) -> Node<T> {
+        if let Some(parent) = self.parent() {
+            parent
+                .get_rc()
+                .children
+                .borrow_mut()
+                .retain(|c| !c.ptr_eq(self));
+        }
+
+        *self.get_rc().parent.borrow_mut() = Weak::new();
+
+        self.clone()
+    }
+
+    /// Moves this node (and its subtree) to be the last child of `new_parent`,
+    /// removing it from its current parent, if any.
+    /// Panics if `new_parent` is this node or one of its descendants,
+    /// since that would create a cycle.
+    pub fn set_parent(&self, new_parent: &Node<T>) {
+        let creates_cycle = core::iter::once(new_parent.clone())
+            .chain(new_parent.ancestors())
+            .any(|n| n.ptr_eq(self));
+
+        if creates_cycle {
+            panic!("Cannot reparent a node under itself or one of its descendants.");
+        }
+
+        self.detach();
+
+        *self.get_rc().parent.borrow_mut() = Rc::downgrade(new_parent.get_rc());
+        new_parent.get_rc().children.borrow_mut().push(self.clone());
+    }
+
+    pub fn downgrade(&self) -> WeakNode<T> {
+        WeakNode(Rc::downgrade(self.get_rc()))
+    }
+
+    /// True if both handles refer to the very same node,
+    /// regardless of whether their data is equal.
+    pub fn ptr_eq(&self, other: &Node<T>) -> bool {
+        Rc::ptr_eq(self.get_rc(), other.get_rc())
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children().is_empty()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.get_rc().parent.borrow().upgrade().is_none()
+    }
+
+    /// The number of edges between this node and the root.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut current = self.parent();
+
+        while let Some(node) = current {
+            depth += 1;
+            current = node.parent();
+        }
+
+        depth
+    }
+
+    /// The number of edges on the longest path from this node down to a leaf.
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = vec![(self.clone(), 0)];
+
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(node.children().iter().map(|c| (c.clone(), depth + 1)));
+        }
+
+        height
+    }
+
+    /// The number of nodes in the subtree rooted at this node,
+    /// including this node itself.
+    pub fn count_subtree_nodes(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self.clone()];
+
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children().iter().cloned());
+        }
+
+        count
+    }
+
+    /// Walks upward from this node's parent to the root, inclusive.
+    /// This node itself is not yielded, so a root yields nothing.
+    pub fn ancestors(&self) -> impl Iterator<Item = Node<T>> {
+        core::iter::successors(self.parent(), |n| n.parent())
+    }
+
+    /// Handles from this node (index 0) up to the root (last index).
+    pub fn path_to_root(&self) -> Vec<Node<T>> {
+        core::iter::once(self.clone())
+            .chain(self.ancestors())
+            .collect()
+    }
+
+    /// The deepest node that is `a` or one of its ancestors,
+    /// and also `b` or one of its ancestors, or `None` if they are in different trees.
+    pub fn lowest_common_ancestor(a: &Node<T>, b: &Node<T>) -> Option<Node<T>> {
+        let a_chain: BTreeSet<*const NodeInternal<T>> = a
+            .path_to_root()
+            .iter()
+            .map(|n| Rc::as_ptr(n.get_rc()))
+            .collect();
+
+        b.path_to_root()
+            .into_iter()
+            .find(|n| a_chain.contains(&Rc::as_ptr(n.get_rc())))
+    }
+
+    /// Builds a structurally identical tree whose data is `f` applied
+    /// to the data of each corresponding node in this subtree.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, mut f: F) -> Node<U> {
+        let mapped_root = Node::new(f(self.data()));
+        let mut stack = vec![(self.clone(), mapped_root.clone())];
+
+        while let Some((source, mut mapped)) = stack.pop() {
+            let source_children = source.children();
+            mapped.add_all_children(source_children.iter().map(|c| f(c.data())));
+
+            stack.extend(
+                source_children
+                    .iter()
+                    .cloned()
+                    .zip(mapped.children().iter().cloned()),
+            );
+        }
+
+        mapped_root
+    }
+
+    /// Computes a value for every node in this subtree from its own data
+    /// and its children's already-folded values (in child order),
+    /// returning the value computed for this node.
+    pub fn fold<U, F: FnMut(&T, &[U]) -> U>(&self, mut f: F) -> U {
+        let mut stack = vec![(self.clone(), false)];
+        let mut results = Vec::new();
+
+        while let Some((node, children_done)) = stack.pop() {
+            if children_done {
+                let child_count = node.children().len();
+                let child_results = results.split_off(results.len() - child_count);
+                results.push(f(node.data(), &child_results));
+            } else {
+                stack.push((node.clone(), true));
+                stack.extend(node.children().iter().rev().map(|c| (c.clone(), false)));
+            }
+        }
+
+        results
+            .pop()
+            .expect("The root's value is always the last one computed.")
+    }
+
+    /// The inverse of `from_parent_indices`: this subtree's data, cloned,
+    /// alongside each element's parent index, with `None` for this node.
+    /// Nodes are numbered in pre-order, starting from this node as index 0,
+    /// so every parent index is smaller than its child's.
+    /// The data is cloned because a borrow of it could not outlive
+    /// the handles to its nodes made while walking the tree.
+    pub fn flatten(&self) -> (Vec<T>, Vec<Option<usize>>)
+    where
+        T: Clone,
+    {
+        let mut data = Vec::new();
+        let mut parents = Vec::new();
+        let mut stack = vec![(self.clone(), None)];
+
+        while let Some((node, parent)) = stack.pop() {
+            let index = data.len();
+            data.push(node.data().clone());
+            parents.push(parent);
+
+            stack.extend(
+                node.children()
+                    .iter()
+                    .rev()
+                    .map(|c| (c.clone(), Some(index))),
+            );
+        }
+
+        (data, parents)
+    }
+
+    /// Renders this subtree as Graphviz DOT text,
+    /// labeling each node with `label` applied to its data.
+    /// Nodes are numbered in pre-order, starting from this node as `node_0`.
+    pub fn to_dot<F: Fn(&T) -> String>(&self, label: F) -> String {
+        let mut dot = String::from("digraph {\n");
+        let mut next_id = 0;
+        let mut stack = vec![(self.clone(), None)];
+
+        while let Some((node, parent_id)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+
+            let escaped = label(node.data())
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"");
+            dot.push_str(&format!("    node_{} [label=\"{}\"];\n", id, escaped));
+
+            if let Some(parent_id) = parent_id {
+                dot.push_str(&format!("    node_{} -> node_{};\n", parent_id, id));
+            }
+
+            stack.extend(node.children().iter().rev().map(|c| (c.clone(), Some(id))));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
     fn get_rc(&self) -> &Rc<NodeInternal<T>> {
         &self.0
     }
@@ -73,14 +661,15 @@ impl<T> Node<T> {
     /// Consider removing this -- if we can expose the data
     /// and the children as mut already, no need to expose the whole
     /// Rc, you can just pick which you need.
+    #[allow(dead_code)]
     fn get_rc_mut(&mut self) -> &mut Rc<NodeInternal<T>> {
         &mut self.0
     }
 
-    fn add_child(&mut self, child_data: T) {
+    pub(crate) fn add_child(&mut self, child_data: T) {
         let internal = NodeInternal {
             data: child_data,
-            parent: Rc::downgrade(self.get_rc()),
+            parent: RefCell::new(Rc::downgrade(self.get_rc())),
             children: RefCell::new(Vec::new()),
         };
 
@@ -91,7 +680,22 @@ impl<T> Node<T> {
     }
 }
 
-impl<T> std::clone::Clone for Node<T> {
+/// Dropping a deep tree with the default recursive drop would use one stack frame per level,
+/// so instead, children are moved into a worklist and dismantled one at a time.
+/// A child still referenced by some other handle is left alone.
+impl<T> Drop for NodeInternal<T> {
+    fn drop(&mut self) {
+        let mut pending = core::mem::take(self.children.get_mut());
+
+        while let Some(Node(rc)) = pending.pop() {
+            if let Ok(mut internal) = Rc::try_unwrap(rc) {
+                pending.append(internal.children.get_mut());
+            }
+        }
+    }
+}
+
+impl<T> Clone for Node<T> {
     fn clone(&self) -> Self {
         Self(Rc::clone(&self.0))
     }
@@ -106,6 +710,24 @@ mod tests {
     #[derive(Debug, PartialEq)]
     struct NoCopy(i32);
 
+    /// Builds the tree:
+    ///         42
+    ///      /      \
+    ///     1        2
+    ///   / | \   / | | \
+    ///  3  4  5  6 7 8  9
+    fn multi_layer_tree() -> Node<NoCopy> {
+        let mut root = Node::new(NoCopy(42));
+
+        root.add_child(NoCopy(1));
+        root.add_child(NoCopy(2));
+
+        root.children_mut()[0].add_all_children(vec![NoCopy(3), NoCopy(4), NoCopy(5)]);
+        root.children_mut()[1].add_all_children(vec![NoCopy(6), NoCopy(7), NoCopy(8), NoCopy(9)]);
+
+        root
+    }
+
     #[test]
     fn new_root_node() {
         let root = Node::new(NoCopy(42));
@@ -239,4 +861,763 @@ mod tests {
 
         assert_eq!(s, test_sum);
     }
+
+    #[test]
+    fn iter_preorder_visit_order() {
+        let root = multi_layer_tree();
+
+        let visited: Vec<i32> = root.iter_preorder().map(|n| n.data().0).collect();
+
+        assert_eq!(vec![42, 1, 3, 4, 5, 2, 6, 7, 8, 9], visited);
+    }
+
+    #[test]
+    fn iter_preorder_lone_root() {
+        let root = Node::new(NoCopy(42));
+
+        let visited: Vec<i32> = root.iter_preorder().map(|n| n.data().0).collect();
+
+        assert_eq!(vec![42], visited);
+    }
+
+    #[test]
+    fn iter_bfs_visit_order() {
+        let root = multi_layer_tree();
+
+        let bfs: Vec<i32> = root.iter_bfs().map(|n| n.data().0).collect();
+        let dfs: Vec<i32> = root.iter_preorder().map(|n| n.data().0).collect();
+
+        assert_eq!(vec![42, 1, 2, 3, 4, 5, 6, 7, 8, 9], bfs);
+        assert_ne!(dfs, bfs);
+    }
+
+    #[test]
+    fn depth_and_height_of_lone_root() {
+        let root = Node::new(NoCopy(42));
+
+        assert_eq!(0, root.depth());
+        assert_eq!(0, root.height());
+    }
+
+    #[test]
+    fn height_of_root_with_one_child() {
+        let mut root = Node::new(NoCopy(42));
+
+        root.add_child(NoCopy(1));
+
+        assert_eq!(1, root.height());
+        assert_eq!(1, root.children()[0].depth());
+    }
+
+    #[test]
+    fn depth_and_height_multiple_layers() {
+        let root = multi_layer_tree();
+        let child = root.children()[1].clone();
+        let grandchild = child.children()[3].clone();
+
+        assert_eq!(2, root.height());
+        assert_eq!(1, child.depth());
+        assert_eq!(1, child.height());
+        assert_eq!(2, grandchild.depth());
+        assert_eq!(0, grandchild.height());
+    }
+
+    #[test]
+    fn is_leaf_and_is_root() {
+        let root = multi_layer_tree();
+        let internal = root.children()[0].clone();
+        let leaf = internal.children()[0].clone();
+
+        assert!(root.is_root());
+        assert!(!root.is_leaf());
+
+        assert!(!internal.is_root());
+        assert!(!internal.is_leaf());
+
+        assert!(!leaf.is_root());
+        assert!(leaf.is_leaf());
+    }
+
+    #[test]
+    fn lone_root_is_leaf_and_root() {
+        let root = Node::new(NoCopy(42));
+
+        assert!(root.is_root());
+        assert!(root.is_leaf());
+    }
+
+    #[test]
+    fn count_subtree_nodes_lone_root() {
+        let root = Node::new(NoCopy(42));
+
+        assert_eq!(1, root.count_subtree_nodes());
+    }
+
+    #[test]
+    fn count_subtree_nodes_three_children_four_grandchildren() {
+        let mut root = Node::new(NoCopy(0));
+
+        root.add_all_children(vec![NoCopy(1), NoCopy(2), NoCopy(3)]);
+
+        for child in root.children_mut().iter_mut() {
+            child.add_all_children(vec![NoCopy(4), NoCopy(5), NoCopy(6), NoCopy(7)]);
+        }
+
+        assert_eq!(16, root.count_subtree_nodes());
+        assert_eq!(5, root.children()[0].count_subtree_nodes());
+    }
+
+    #[test]
+    fn ancestors_from_leaf() {
+        let root = multi_layer_tree();
+        let leaf = root.children()[1].children()[2].clone();
+
+        let ancestors: Vec<i32> = leaf.ancestors().map(|n| n.data().0).collect();
+
+        assert_eq!(vec![2, 42], ancestors);
+    }
+
+    #[test]
+    fn ancestors_of_root_is_empty() {
+        let root = multi_layer_tree();
+
+        assert_eq!(0, root.ancestors().count());
+    }
+
+    #[test]
+    fn path_to_root_from_deep_node() {
+        let root = multi_layer_tree();
+        let leaf = root.children()[0].children()[1].clone();
+
+        let path = leaf.path_to_root();
+
+        assert_eq!(3, path.len());
+        assert_eq!(NoCopy(4), *path[0].data());
+        assert_eq!(NoCopy(1), *path[1].data());
+        assert_eq!(NoCopy(42), *path[2].data());
+    }
+
+    #[test]
+    fn path_to_root_of_root() {
+        let root = Node::new(NoCopy(42));
+
+        let path = root.path_to_root();
+
+        assert_eq!(1, path.len());
+        assert_eq!(NoCopy(42), *path[0].data());
+    }
+
+    #[test]
+    fn remove_middle_child() {
+        let mut root = Node::new(NoCopy(42));
+
+        root.add_all_children(vec![NoCopy(1), NoCopy(2), NoCopy(3)]);
+
+        let removed = root.remove_child(1).expect("Index 1 is in bounds.");
+
+        assert_eq!(NoCopy(2), *removed.data());
+        assert_eq!(2, root.children().len());
+        assert_eq!(NoCopy(1), *root.children()[0].data());
+        assert_eq!(NoCopy(3), *root.children()[1].data());
+    }
+
+    #[test]
+    fn remove_child_out_of_bounds() {
+        let mut root = Node::new(NoCopy(42));
+
+        root.add_child(NoCopy(1));
+
+        assert!(root.remove_child(1).is_none());
+        assert_eq!(1, root.children().len());
+    }
+
+    #[test]
+    fn detached_child_is_root_and_keeps_subtree() {
+        let root = multi_layer_tree();
+        let child = root.children()[1].clone();
+
+        let detached = child.detach();
+
+        assert!(detached.is_root());
+        assert_eq!(NoCopy(2), *detached.data());
+        assert_eq!(4, detached.children().len());
+        assert_eq!(1, detached.children()[0].depth());
+    }
+
+    #[test]
+    fn detached_child_is_removed_from_parent() {
+        let root = multi_layer_tree();
+        let child = root.children()[0].clone();
+
+        child.detach();
+
+        assert_eq!(1, root.children().len());
+        assert_eq!(NoCopy(2), *root.children()[0].data());
+    }
+
+    #[test]
+    fn detach_root_is_noop() {
+        let root = multi_layer_tree();
+
+        let detached = root.detach();
+
+        assert!(detached.is_root());
+        assert_eq!(2, detached.children().len());
+    }
+
+    #[test]
+    fn set_parent_moves_subtree() {
+        let root = multi_layer_tree();
+        let first = root.children()[0].clone();
+        let second = root.children()[1].clone();
+
+        first.set_parent(&second);
+
+        assert_eq!(1, root.children().len());
+        assert_eq!(5, second.children().len());
+        assert_eq!(NoCopy(2), *first.parent().unwrap().data());
+        assert_eq!(2, first.depth());
+        assert_eq!(3, first.children()[0].depth());
+    }
+
+    #[test]
+    fn set_parent_of_detached_root() {
+        let root = multi_layer_tree();
+        let other = Node::new(NoCopy(100));
+
+        other.set_parent(&root);
+
+        assert_eq!(3, root.children().len());
+        assert_eq!(NoCopy(42), *other.parent().unwrap().data());
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_parent_rejects_cycle() {
+        let root = multi_layer_tree();
+        let grandchild = root.children()[0].children()[0].clone();
+
+        root.set_parent(&grandchild);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_parent_rejects_self() {
+        let root = multi_layer_tree();
+
+        root.set_parent(&root);
+    }
+
+    #[test]
+    fn map_preserves_structure() {
+        let counts: Node<i32> = multi_layer_tree().map(|d| d.0);
+
+        let mapped: Node<String> = counts.map(|c| format!("n{}", c));
+
+        assert_eq!("n42", mapped.data());
+        assert!(mapped.is_root());
+        assert_eq!(counts.count_subtree_nodes(), mapped.count_subtree_nodes());
+
+        let expected: Vec<String> = counts
+            .iter_preorder()
+            .map(|n| format!("n{}", n.data()))
+            .collect();
+        let result: Vec<String> = mapped.iter_preorder().map(|n| n.data().clone()).collect();
+        assert_eq!(expected, result);
+
+        let grandchild = mapped.children()[1].children()[3].clone();
+        assert_eq!("n9", grandchild.data());
+        assert_eq!("n2", grandchild.parent().unwrap().data());
+    }
+
+    #[test]
+    fn fold_sums_like_complex_walk() {
+        let mut v = 0;
+        let mut s = 0;
+        let sum = &mut s;
+        let mut i = move || {
+            v += 1;
+            *sum += v;
+            NoCopy(v)
+        };
+
+        let mut root = Node::new(i());
+
+        root.add_all_children(vec![i(), i(), i()]);
+
+        for child in root.children_mut().iter_mut() {
+            child.add_all_children(vec![i(), i(), i(), i()]);
+        }
+
+        let folded = root.fold(|data, child_sums: &[i32]| data.0 + child_sums.iter().sum::<i32>());
+
+        assert_eq!(s, folded);
+    }
+
+    #[test]
+    fn fold_sees_children_in_order() {
+        let root = multi_layer_tree();
+
+        let folded = root.fold(|data, children: &[String]| {
+            if children.is_empty() {
+                data.0.to_string()
+            } else {
+                format!("{}({})", data.0, children.join(","))
+            }
+        });
+
+        assert_eq!("42(1(3,4,5),2(6,7,8,9))", folded);
+    }
+
+    #[test]
+    fn ptr_eq_same_node() {
+        let root = Node::new(NoCopy(42));
+        let handle = root.clone();
+
+        assert!(root.ptr_eq(&handle));
+    }
+
+    #[test]
+    fn ptr_eq_distinct_nodes_with_equal_data() {
+        let a = Node::new(NoCopy(42));
+        let b = Node::new(NoCopy(42));
+
+        assert_eq!(a.data(), b.data());
+        assert!(!a.ptr_eq(&b));
+    }
+
+    #[test]
+    fn iter_leaves_yields_grandchildren() {
+        let root = multi_layer_tree();
+
+        let leaves: Vec<i32> = root.iter_leaves().map(|n| n.data().0).collect();
+
+        assert_eq!(vec![3, 4, 5, 6, 7, 8, 9], leaves);
+    }
+
+    #[test]
+    fn iter_leaves_lone_root() {
+        let root = Node::new(NoCopy(42));
+
+        let leaves: Vec<i32> = root.iter_leaves().map(|n| n.data().0).collect();
+
+        assert_eq!(vec![42], leaves);
+    }
+
+    #[test]
+    fn dropping_deep_chain_does_not_overflow() {
+        let root = Node::new(NoCopy(0));
+        let mut current = root.clone();
+
+        for i in 1..100_000 {
+            current.add_child(NoCopy(i));
+            let next = current.children()[0].clone();
+            current = next;
+        }
+
+        assert_eq!(99_999, current.depth());
+
+        drop(current);
+        drop(root);
+    }
+
+    #[test]
+    fn dropping_root_keeps_externally_held_subtree() {
+        let root = multi_layer_tree();
+        let child = root.children()[1].clone();
+
+        drop(root);
+
+        assert_eq!(4, child.children().len());
+        assert_eq!(NoCopy(9), *child.children()[3].data());
+        assert!(child.parent().is_none());
+    }
+
+    #[test]
+    fn prune_children_where_subset() {
+        let mut root = Node::new(NoCopy(42));
+
+        root.add_all_children(vec![NoCopy(1), NoCopy(5), NoCopy(2), NoCopy(7)]);
+        root.prune_children_where(|d| d.0 > 3);
+
+        let remaining: Vec<i32> = root.children().iter().map(|c| c.data().0).collect();
+        assert_eq!(vec![1, 2], remaining);
+    }
+
+    #[test]
+    fn prune_children_where_none_match() {
+        let mut root = multi_layer_tree();
+
+        root.prune_children_where(|d| d.0 > 100);
+
+        assert_eq!(2, root.children().len());
+        assert_eq!(10, root.count_subtree_nodes());
+    }
+
+    #[test]
+    fn prune_children_where_all_match() {
+        let mut root = multi_layer_tree();
+
+        root.prune_children_where(|d| d.0 > 0);
+
+        assert!(root.is_leaf());
+        assert_eq!(1, root.count_subtree_nodes());
+    }
+
+    #[test]
+    fn to_dot_node_and_edge_lines() {
+        let root = multi_layer_tree();
+
+        let dot = root.to_dot(|d| d.0.to_string());
+
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert_eq!(10, dot.lines().filter(|l| l.contains("[label=")).count());
+        assert_eq!(9, dot.lines().filter(|l| l.contains("->")).count());
+        assert!(dot.contains("node_0 [label=\"42\"];"));
+        assert!(dot.contains("node_0 -> node_1;"));
+        assert!(dot.contains("node_1 -> node_2;"));
+        assert!(dot.contains("node_0 -> node_5;"));
+    }
+
+    #[test]
+    fn to_dot_escapes_quotes() {
+        let root = Node::new(NoCopy(1));
+
+        let dot = root.to_dot(|_| r#"say "hi""#.to_string());
+
+        assert!(dot.contains(r#"node_0 [label="say \"hi\""];"#));
+    }
+
+    #[test]
+    fn child_count_and_nth_child_in_range() {
+        let root = multi_layer_tree();
+
+        assert_eq!(2, root.child_count());
+
+        let second = root.nth_child(1).expect("Index 1 is in range.");
+        assert_eq!(NoCopy(2), *second.data());
+        assert_eq!(4, second.child_count());
+    }
+
+    #[test]
+    fn from_parent_indices_builds_tree() {
+        let data = vec![NoCopy(1), NoCopy(42), NoCopy(3), NoCopy(2)];
+        let parents = [Some(1), None, Some(0), Some(1)];
+
+        let root = Node::from_parent_indices(data, &parents).unwrap();
+
+        assert_eq!(NoCopy(42), *root.data());
+        let children = root.children();
+        assert_eq!(2, children.len());
+        assert_eq!(NoCopy(1), *children[0].data());
+        assert_eq!(NoCopy(2), *children[1].data());
+        assert_eq!(NoCopy(3), *children[0].children()[0].data());
+        assert_eq!(
+            NoCopy(1),
+            *children[0].children()[0].parent().unwrap().data()
+        );
+        assert_eq!(4, root.count_subtree_nodes());
+    }
+
+    #[test]
+    fn flatten_numbers_nodes_in_preorder() {
+        let root = multi_layer_tree().map(|d| d.0);
+
+        let (data, parents) = root.flatten();
+
+        assert_eq!(vec![42, 1, 3, 4, 5, 2, 6, 7, 8, 9], data);
+        assert_eq!(
+            vec![
+                None,
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(0),
+                Some(5),
+                Some(5),
+                Some(5),
+                Some(5)
+            ],
+            parents
+        );
+
+        let rebuilt = Node::from_parent_indices(data, &parents).unwrap();
+        assert_eq!(root.flatten(), rebuilt.flatten());
+        assert_eq!(
+            vec![6, 7, 8, 9],
+            rebuilt.children()[1]
+                .children()
+                .iter()
+                .map(|c| *c.data())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn from_parent_indices_without_root() {
+        let data = vec![NoCopy(1), NoCopy(2)];
+
+        let result = Node::from_parent_indices(data, &[Some(1), Some(0)]);
+
+        assert_eq!(Some(ParentIndicesError::NoRoot), result.err());
+    }
+
+    #[test]
+    fn from_parent_indices_with_cycle() {
+        let data = vec![NoCopy(42), NoCopy(1), NoCopy(2)];
+
+        let result = Node::from_parent_indices(data, &[None, Some(2), Some(1)]);
+
+        assert_eq!(Some(ParentIndicesError::Cycle), result.err());
+    }
+
+    #[test]
+    fn from_parent_indices_with_two_roots_or_bad_index() {
+        let two_roots = Node::from_parent_indices(vec![NoCopy(1), NoCopy(2)], &[None, None]);
+        let bad_index = Node::from_parent_indices(vec![NoCopy(1), NoCopy(2)], &[None, Some(5)]);
+
+        assert_eq!(Some(ParentIndicesError::MultipleRoots), two_roots.err());
+        assert_eq!(
+            Some(ParentIndicesError::ParentOutOfRange { index: 1 }),
+            bad_index.err()
+        );
+    }
+
+    #[test]
+    fn reserve_children_grows_capacity() {
+        let mut root = Node::new(NoCopy(42));
+        root.add_child(NoCopy(1));
+
+        root.reserve_children(50);
+
+        assert!(root.get_rc().children.borrow().capacity() >= 51);
+        assert_eq!(1, root.child_count());
+    }
+
+    #[test]
+    fn siblings_exclude_self() {
+        let root = multi_layer_tree();
+        let middle = root.children()[0].nth_child(1).unwrap();
+
+        let siblings: Vec<_> = middle.siblings().collect();
+
+        assert_eq!(2, siblings.len());
+        assert_eq!(NoCopy(3), *siblings[0].data());
+        assert_eq!(NoCopy(5), *siblings[1].data());
+        assert!(siblings.iter().all(|s| !s.ptr_eq(&middle)));
+    }
+
+    #[test]
+    fn root_has_no_siblings() {
+        assert_eq!(0, multi_layer_tree().siblings().count());
+    }
+
+    #[test]
+    fn lowest_common_ancestor_of_two_leaves() {
+        let root = multi_layer_tree();
+        let three = root.children()[0].nth_child(0).unwrap();
+        let five = root.children()[0].nth_child(2).unwrap();
+        let nine = root.children()[1].nth_child(3).unwrap();
+
+        let near = Node::lowest_common_ancestor(&three, &five).unwrap();
+        let far = Node::lowest_common_ancestor(&three, &nine).unwrap();
+
+        assert_eq!(NoCopy(1), *near.data());
+        assert!(far.ptr_eq(&root));
+    }
+
+    #[test]
+    fn lowest_common_ancestor_of_node_and_its_ancestor() {
+        let root = multi_layer_tree();
+        let one = root.nth_child(0).unwrap();
+        let four = one.nth_child(1).unwrap();
+
+        assert!(Node::lowest_common_ancestor(&four, &one)
+            .unwrap()
+            .ptr_eq(&one));
+        assert!(Node::lowest_common_ancestor(&one, &four)
+            .unwrap()
+            .ptr_eq(&one));
+    }
+
+    #[test]
+    fn lowest_common_ancestor_of_unrelated_trees() {
+        let first = multi_layer_tree();
+        let second = multi_layer_tree();
+
+        let a = first.nth_child(0).unwrap();
+        let b = second.nth_child(0).unwrap();
+
+        assert!(Node::lowest_common_ancestor(&a, &b).is_none());
+    }
+
+    #[test]
+    fn weak_node_upgrades_while_node_lives() {
+        let root = multi_layer_tree();
+        let weak = root.nth_child(1).unwrap().downgrade();
+
+        let upgraded = weak.upgrade().unwrap();
+
+        assert!(upgraded.ptr_eq(&root.children()[1]));
+    }
+
+    #[test]
+    fn weak_node_does_not_keep_node_alive() {
+        let root = multi_layer_tree();
+        let weak_root = root.downgrade();
+        let weak_child = root.nth_child(0).unwrap().downgrade();
+
+        drop(root);
+
+        assert!(weak_root.upgrade().is_none());
+        assert!(weak_child.upgrade().is_none());
+    }
+
+    #[test]
+    fn find_locates_grandchild() {
+        let root = multi_layer_tree();
+
+        let found = root.find(|d| d.0 == 7).unwrap();
+
+        assert_eq!(NoCopy(7), *found.data());
+        assert!(found.parent().unwrap().ptr_eq(&root.children()[1]));
+    }
+
+    #[test]
+    fn find_without_match() {
+        let root = multi_layer_tree();
+
+        assert!(root.find(|d| d.0 > 100).is_none());
+    }
+
+    #[test]
+    fn into_subtree_keeps_matching_subtree() {
+        let root = multi_layer_tree();
+        let weak_root = root.downgrade();
+
+        let subtree = root.into_subtree(|d| d.0 == 2).unwrap();
+
+        assert!(subtree.is_root());
+        assert_eq!(0, subtree.ancestors().count());
+        let children: Vec<i32> = subtree.children().iter().map(|c| c.data().0).collect();
+        assert_eq!(vec![6, 7, 8, 9], children);
+        assert!(subtree.children()[0].parent().unwrap().ptr_eq(&subtree));
+        assert!(weak_root.upgrade().is_none());
+    }
+
+    #[test]
+    fn into_subtree_skips_matching_root() {
+        let root = multi_layer_tree();
+
+        // The root matches too, but only its descendants are searched.
+        let subtree = root.into_subtree(|d| d.0 == 42 || d.0 == 2).unwrap();
+
+        assert_eq!(NoCopy(2), *subtree.data());
+        assert!(subtree.is_root());
+    }
+
+    #[test]
+    fn into_subtree_without_match() {
+        assert!(multi_layer_tree().into_subtree(|d| d.0 > 100).is_none());
+    }
+
+    #[test]
+    fn nth_child_out_of_range() {
+        let root = multi_layer_tree();
+
+        assert!(root.nth_child(2).is_none());
+        assert!(Node::new(NoCopy(1)).nth_child(0).is_none());
+        assert_eq!(0, Node::new(NoCopy(1)).child_count());
+    }
+
+    #[test]
+    fn clear_children_keeps_data_and_parent() {
+        let root = multi_layer_tree();
+        let mut child = root.children()[0].clone();
+        let data = child.data().0;
+
+        child.clear_children();
+
+        assert_eq!(0, child.child_count());
+        assert_eq!(data, child.data().0);
+        assert!(child.parent().unwrap().ptr_eq(&root));
+        assert_eq!(2, root.child_count());
+    }
+
+    #[test]
+    fn clear_children_of_deep_chain_does_not_overflow() {
+        let mut root = Node::new(NoCopy(0));
+        let mut current = root.clone();
+
+        for i in 1..100_000 {
+            current.add_child(NoCopy(i));
+            let next = current.children()[0].clone();
+            current = next;
+        }
+        drop(current);
+
+        root.clear_children();
+
+        assert!(root.is_leaf());
+        assert_eq!(NoCopy(0), *root.data());
+    }
+
+    #[test]
+    fn pool_reuses_nodes_across_regrowth() {
+        let mut pool = NodePool::new();
+        let mut root = Node::new(NoCopy(0));
+
+        for round in 0..100 {
+            root.add_all_children_from(&mut pool, (1..=10).map(NoCopy));
+            for child in root.children_mut().iter_mut() {
+                child.add_all_children_from(&mut pool, (0..3).map(|_| NoCopy(round)));
+            }
+
+            assert_eq!(41, root.count_subtree_nodes());
+            assert!(root.children()[9].children()[2]
+                .parent()
+                .unwrap()
+                .ptr_eq(&root.children()[9]));
+            assert_eq!(NoCopy(round), *root.children()[0].children()[0].data());
+
+            root.clear_children_into(&mut pool);
+            assert_eq!(40, pool.len());
+        }
+
+        assert_eq!(40, pool.allocated());
+        assert_eq!(99 * 40, pool.reused());
+    }
+
+    #[test]
+    fn pool_leaves_externally_held_nodes_alone() {
+        let mut pool = NodePool::new();
+        let mut root = multi_layer_tree();
+        let held = root.children()[1].clone();
+        let held_size = held.count_subtree_nodes();
+
+        root.clear_children_into(&mut pool);
+        assert_eq!(4, pool.len());
+
+        root.add_child_from(&mut pool, NoCopy(99));
+
+        assert_eq!(5, held_size);
+        assert_eq!(held_size, held.count_subtree_nodes());
+        assert_eq!(NoCopy(99), *root.children()[0].data());
+        assert_eq!(0, pool.allocated());
+    }
+
+    #[test]
+    fn pool_drops_weakly_held_nodes_instead_of_reusing_them() {
+        let mut pool = NodePool::new();
+        let mut root = multi_layer_tree();
+        let weak = root.children()[1].downgrade();
+
+        root.clear_children_into(&mut pool);
+
+        // Only the first child and its three children are recycled.
+        assert_eq!(4, pool.len());
+        assert!(weak.upgrade().is_none());
+    }
 }
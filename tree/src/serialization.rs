@@ -0,0 +1,154 @@
+//! Serde support for `Node` trees, enabled by the `serde` feature.
+//!
+//! A tree is written as a flat pre-order list of `{ data, children }` entries,
+//! where `children` is the number of children the entry's node has,
+//! so that neither writing nor reading a tree recurses once per level
+//! and trees deeper than a format's recursion limit still round-trip.
+//! Parent links are not serialized; they are rebuilt
+//! when the list is turned back into a tree.
+
+use crate::node::Node;
+use serde::de::Error;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl<T: Serialize> Serialize for Node<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.count_subtree_nodes()))?;
+        for node in self.iter_preorder() {
+            seq.serialize_element(&EntryRef {
+                data: node.data(),
+                children: node.child_count(),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Node<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<Entry<T>>::deserialize(deserializer)?;
+
+        into_node(entries).map_err(D::Error::custom)
+    }
+}
+
+/// A node as it is written, borrowing its data.
+#[derive(Serialize)]
+#[serde(rename = "Node")]
+struct EntryRef<'a, T> {
+    data: &'a T,
+    children: usize,
+}
+
+/// A node as it is read back, without any parent links.
+#[derive(Deserialize)]
+#[serde(rename = "Node")]
+struct Entry<T> {
+    data: T,
+    children: usize,
+}
+
+/// Rebuilds the tree from its pre-order entries, keeping the nodes still owed
+/// children on a stack, and letting `add_child` restore each child's parent link.
+fn into_node<T>(entries: Vec<Entry<T>>) -> Result<Node<T>, &'static str> {
+    let mut entries = entries.into_iter();
+    let first = entries
+        .next()
+        .ok_or("A serialized tree has at least a root.")?;
+    let root = Node::new(first.data);
+    let mut owed = vec![(root.clone(), first.children)];
+
+    for entry in entries {
+        while owed.last().is_some_and(|&(_, remaining)| remaining == 0) {
+            owed.pop();
+        }
+        let (parent, remaining) = owed
+            .last_mut()
+            .ok_or("A serialized tree has more nodes than its child counts allow.")?;
+
+        *remaining -= 1;
+        parent.add_child(entry.data);
+        let child = parent.nth_child(parent.child_count() - 1).unwrap();
+        owed.push((child, entry.children));
+    }
+
+    if owed.iter().any(|&(_, remaining)| remaining > 0) {
+        return Err("A serialized tree has fewer nodes than its child counts call for.");
+    }
+
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_level_tree() -> Node<i32> {
+        let mut root = Node::new(42);
+
+        root.add_all_children(vec![1, 2]);
+        root.children_mut()[0].add_all_children(vec![3, 4, 5]);
+        root.children_mut()[1].add_all_children(vec![6, 7, 8, 9]);
+
+        root
+    }
+
+    #[test]
+    fn serializes_flat_preorder_entries() {
+        let mut root = Node::new(1);
+        root.add_all_children(vec![2]);
+
+        let json = serde_json::to_string(&root).unwrap();
+
+        assert_eq!(r#"[{"data":1,"children":1},{"data":2,"children":0}]"#, json);
+    }
+
+    #[test]
+    fn round_trip_preserves_structure_and_parents() {
+        let original = three_level_tree();
+
+        let json = serde_json::to_string(&original).unwrap();
+        let restored: Node<i32> = serde_json::from_str(&json).unwrap();
+
+        let expected: Vec<i32> = original.iter_preorder().map(|n| *n.data()).collect();
+        let actual: Vec<i32> = restored.iter_preorder().map(|n| *n.data()).collect();
+        assert_eq!(expected, actual);
+
+        assert!(restored.is_root());
+        for child in restored.children().iter() {
+            assert!(child.parent().unwrap().ptr_eq(&restored));
+
+            for grandchild in child.children().iter() {
+                assert!(grandchild.parent().unwrap().ptr_eq(child));
+                assert_eq!(2, grandchild.depth());
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_of_a_chain_deeper_than_the_recursion_limit() {
+        let root = Node::new(0);
+        let mut leaf = root.clone();
+        for i in 1..1000 {
+            leaf.add_all_children(vec![i]);
+            leaf = leaf.nth_child(0).unwrap();
+        }
+
+        let json = serde_json::to_string(&root).unwrap();
+        let restored: Node<i32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(1000, restored.count_subtree_nodes());
+        assert_eq!(999, restored.iter_leaves().next().unwrap().depth());
+    }
+
+    #[test]
+    fn mismatched_child_counts_are_rejected() {
+        let too_many = r#"[{"data":1,"children":0},{"data":2,"children":0}]"#;
+        let too_few = r#"[{"data":1,"children":2},{"data":2,"children":0}]"#;
+
+        assert!(serde_json::from_str::<Node<i32>>(too_many).is_err());
+        assert!(serde_json::from_str::<Node<i32>>(too_few).is_err());
+        assert!(serde_json::from_str::<Node<i32>>("[]").is_err());
+    }
+}
@@ -0,0 +1,367 @@
+use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use std::hint;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A slot that may be written to exactly once,
+/// and read from any number of times afterwards.
+pub struct WriteOnceLock<T> {
+    data: AtomicRefCell<Option<T>>,
+    /// Always `None`, and lent out by `read` until `data` is ready,
+    /// so that a reader never holds a borrow the writer could be waiting on.
+    empty: AtomicRefCell<Option<T>>,
+    has_written: AtomicBool,
+    /// Whether the claimed write has been made, or at least handed out by `write_once`.
+    is_ready: AtomicBool,
+}
+
+impl<T> WriteOnceLock<T> {
+    pub fn new() -> Self {
+        Self {
+            data: AtomicRefCell::new(None),
+            empty: AtomicRefCell::new(None),
+            has_written: AtomicBool::new(false),
+            is_ready: AtomicBool::new(false),
+        }
+    }
+
+    /// The stored value, or `None` if no write has finished yet,
+    /// including while the borrow handed out by `write_once` is still held.
+    /// Holding on to the result never blocks a write,
+    /// though it will keep reading `None` if taken before the write.
+    pub fn read(&self) -> AtomicRef<'_, Option<T>> {
+        if self.is_ready.load(Ordering::Acquire) {
+            if let Ok(data) = self.data.try_borrow() {
+                return data;
+            }
+        }
+
+        self.empty.borrow()
+    }
+
+    /// Claims the single write for the caller and hands out
+    /// the mutable borrow to fill it in.
+    /// Panics if the slot was already claimed by an earlier write;
+    /// use this when a double write would be a bug,
+    /// and `try_write` when losing the race is expected.
+    pub fn write_once(&self) -> AtomicRefMut<'_, Option<T>> {
+        if !self.claim() {
+            panic!("Attempted to write to a WriteOnceLock more than once.");
+        }
+
+        let data = self.borrow_claimed();
+        self.is_ready.store(true, Ordering::Release);
+
+        data
+    }
+
+    /// Returns the stored value, first initializing it with `f`
+    /// if nothing has been written yet.
+    /// Only one caller at a time runs its initializer; any concurrent callers
+    /// spin until the value is available. If the initializer panics,
+    /// the slot is released again, and one of the waiting or later callers
+    /// runs its own initializer instead.
+    /// If the slot was claimed by `write_once` and left empty,
+    /// this will spin forever.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> AtomicRef<'_, T> {
+        let mut f = Some(f);
+
+        loop {
+            if f.is_some() && self.claim() {
+                let release = ReleaseOnUnwind(&self.has_written);
+                let value = f.take().unwrap()();
+                self.store(value);
+                mem::forget(release);
+            }
+
+            if self.is_ready.load(Ordering::Acquire) {
+                if let Ok(data) = self.data.try_borrow() {
+                    if data.is_some() {
+                        return AtomicRef::map(data, |d| d.as_ref().unwrap());
+                    }
+                }
+            }
+
+            hint::spin_loop();
+        }
+    }
+
+    /// Stores `value` if nothing has been written yet.
+    /// Otherwise, hands `value` back to the caller without panicking.
+    pub fn try_write(&self, value: T) -> Result<(), T> {
+        if !self.claim() {
+            return Err(value);
+        }
+
+        self.store(value);
+
+        Ok(())
+    }
+
+    pub fn has_written(&self) -> bool {
+        self.has_written.load(Ordering::Acquire)
+    }
+
+    /// Consumes the lock, returning the stored value if one was written.
+    pub fn into_inner(self) -> Option<T> {
+        self.data.into_inner()
+    }
+
+    /// Atomically flips `has_written`, returning true only for the one caller
+    /// that performed the flip.
+    fn claim(&self) -> bool {
+        self.has_written
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Called only by the claimer, who is the only one to borrow `data`
+    /// before it is ready, so the borrow cannot conflict with any other.
+    fn borrow_claimed(&self) -> AtomicRefMut<'_, Option<T>> {
+        self.data
+            .try_borrow_mut()
+            .expect("A claimed WriteOnceLock is never borrowed before it is written.")
+    }
+
+    fn store(&self, value: T) {
+        *self.borrow_claimed() = Some(value);
+        self.is_ready.store(true, Ordering::Release);
+    }
+}
+
+/// Un-claims a `WriteOnceLock` if dropped, which only happens
+/// when an initializer panics before it can be forgotten.
+struct ReleaseOnUnwind<'a>(&'a AtomicBool);
+
+impl Drop for ReleaseOnUnwind<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Default for WriteOnceLock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn has_written_is_false_before_write() {
+        let lock = WriteOnceLock::<i32>::new();
+
+        assert!(!lock.has_written());
+        assert!(lock.read().is_none());
+    }
+
+    #[test]
+    fn has_written_is_true_after_write() {
+        let lock = WriteOnceLock::new();
+
+        *lock.write_once() = Some(42);
+
+        assert!(lock.has_written());
+        assert_eq!(Some(42), *lock.read());
+    }
+
+    #[test]
+    fn read_while_write_once_is_held_sees_nothing() {
+        let lock = WriteOnceLock::new();
+
+        let mut slot = lock.write_once();
+        thread::scope(|s| {
+            s.spawn(|| assert!(lock.read().is_none()));
+        });
+        assert!(lock.read().is_none());
+        *slot = Some(42);
+        drop(slot);
+
+        assert_eq!(Some(42), *lock.read());
+    }
+
+    #[test]
+    fn has_written_is_true_even_if_nothing_stored() {
+        let lock = WriteOnceLock::<i32>::new();
+
+        let _ = lock.write_once();
+
+        assert!(lock.has_written());
+    }
+
+    #[test]
+    #[should_panic]
+    fn second_write_panics() {
+        let lock = WriteOnceLock::new();
+
+        *lock.write_once() = Some(1);
+        *lock.write_once() = Some(2);
+    }
+
+    #[test]
+    fn two_threads_racing_exactly_one_succeeds() {
+        let lock = Arc::new(WriteOnceLock::<usize>::new());
+
+        let handles: Vec<_> = (0..2)
+            .map(|i| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || {
+                    *lock.write_once() = Some(i);
+                })
+            })
+            .collect();
+
+        let successes = handles
+            .into_iter()
+            .map(|h| h.join())
+            .filter(|r| r.is_ok())
+            .count();
+
+        assert_eq!(1, successes);
+        assert!(lock.has_written());
+        assert!(lock.read().is_some());
+    }
+
+    #[test]
+    fn get_or_init_initializes_empty_lock() {
+        let lock = WriteOnceLock::new();
+
+        assert_eq!(7, *lock.get_or_init(|| 7));
+        assert!(lock.has_written());
+    }
+
+    #[test]
+    fn get_or_init_returns_existing_value() {
+        let lock = WriteOnceLock::new();
+
+        *lock.write_once() = Some(1);
+
+        assert_eq!(1, *lock.get_or_init(|| 2));
+    }
+
+    #[test]
+    fn get_or_init_runs_initializer_once_across_threads() {
+        let lock = Arc::new(WriteOnceLock::<usize>::new());
+        let init_count = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..10)
+            .map(|i| {
+                let lock = Arc::clone(&lock);
+                let init_count = Arc::clone(&init_count);
+                thread::spawn(move || {
+                    *lock.get_or_init(|| {
+                        init_count.fetch_add(1, Ordering::SeqCst);
+                        i
+                    })
+                })
+            })
+            .collect();
+
+        let seen: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(1, init_count.load(Ordering::SeqCst));
+        assert!(seen.iter().all(|&v| v == seen[0]));
+    }
+
+    #[test]
+    fn get_or_init_after_panicking_initializer_retries() {
+        let lock = WriteOnceLock::new();
+
+        let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+            lock.get_or_init(|| panic!("initializer failed"));
+        }));
+
+        assert!(panicked.is_err());
+        assert!(!lock.has_written());
+        assert_eq!(3, *lock.get_or_init(|| 3));
+        assert_eq!(Err(4), lock.try_write(4));
+    }
+
+    #[test]
+    fn default_starts_empty() {
+        let lock = WriteOnceLock::<i32>::default();
+
+        assert!(!lock.has_written());
+        assert!(lock.read().is_none());
+    }
+
+    #[test]
+    fn into_inner_without_write() {
+        let lock = WriteOnceLock::<i32>::new();
+
+        assert_eq!(None, lock.into_inner());
+    }
+
+    #[test]
+    fn into_inner_after_write() {
+        let lock = WriteOnceLock::new();
+
+        *lock.write_once() = Some(42);
+
+        assert_eq!(Some(42), lock.into_inner());
+    }
+
+    #[test]
+    fn try_write_first_call_succeeds() {
+        let lock = WriteOnceLock::new();
+
+        assert_eq!(Ok(()), lock.try_write(5));
+        assert!(lock.has_written());
+        assert_eq!(Some(5), *lock.read());
+    }
+
+    #[test]
+    fn try_write_second_call_returns_rejected_value() {
+        let lock = WriteOnceLock::new();
+
+        assert_eq!(Ok(()), lock.try_write(5));
+        assert_eq!(Err(6), lock.try_write(6));
+        assert_eq!(Some(5), *lock.read());
+    }
+
+    #[test]
+    fn try_write_while_holding_a_read_succeeds() {
+        let lock = WriteOnceLock::new();
+
+        let before = lock.read();
+        assert_eq!(Ok(()), lock.try_write(5));
+
+        assert!(before.is_none());
+        assert_eq!(Some(5), *lock.read());
+    }
+
+    #[test]
+    fn try_write_after_write_once_is_rejected() {
+        let lock = WriteOnceLock::new();
+
+        *lock.write_once() = Some(1);
+
+        assert_eq!(Err(2), lock.try_write(2));
+    }
+
+    #[test]
+    fn try_write_racing_threads_exactly_one_succeeds() {
+        let lock = Arc::new(WriteOnceLock::<usize>::new());
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || lock.try_write(i).is_ok())
+            })
+            .collect();
+
+        let successes = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .filter(|&ok| ok)
+            .count();
+
+        assert_eq!(1, successes);
+    }
+}